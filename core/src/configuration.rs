@@ -0,0 +1,621 @@
+use crate::http::NetworkSettings;
+use crate::locales::{init_locale, Locale, LocaleChoice, Nester};
+use crate::quake_file::QuakeFile;
+use crate::quake_paths::{required_mission_packs, MissionPack};
+use chrono::Utc;
+use derive_builder::Builder;
+use dirs::config_dir;
+use getset::{Getters, Setters};
+use log::*;
+use quick_xml::de::from_reader;
+use quick_xml::se::to_writer;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{rename, File};
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE_NAME: &str = "config.xml";
+const LOCAL_MAPS_FILE_NAME: &str = "installedMaps.xml";
+const DATABASE_META_FILE_NAME: &str = "databaseMeta.xml";
+const SEEN_MAPS_FILE_NAME: &str = "seenMaps.xml";
+// the engine set by quake_exe, always there and never stored as a profile
+pub const DEFAULT_ENGINE_NAME: &str = "default";
+
+#[derive(Clone, Debug, Deserialize, Getters, Serialize, Setters)]
+#[getset(get = "pub", set = "pub")]
+pub struct Configuration {
+    #[serde(default = "Default::default")]
+    quake_dir: String,
+    #[serde(default = "Default::default")]
+    quake_exe: String,
+    #[serde(default = "Default::default")]
+    download_dir: String,
+    rogue_installed: bool,
+    hip_installed: bool,
+    is_offline: bool,
+    config_dir: PathBuf,
+    image_cache_dir: PathBuf,
+    image_resources_dir: PathBuf,
+    locale_resources_dir: PathBuf,
+    logs_dir: PathBuf,
+    current_locale_choice: Nester,
+    #[serde(skip)] // don't store this in the file. Makes the file hard to read.
+    current_locale: Locale,
+    date_format: String,
+    #[serde(default = "default_max_concurrent_downloads")]
+    max_concurrent_downloads: u8,
+    // 0 turns the automatic refresh off
+    #[serde(default = "default_database_max_age_hours")]
+    database_max_age_hours: u32,
+    // tried in order, the next one gets a go when a request fails
+    #[serde(rename = "mirror", default = "default_mirrors")]
+    mirrors: Vec<Mirror>,
+    // passed to the engine for every map
+    #[serde(default = "Default::default")]
+    launch_options: String,
+    // engines on top of the default one, maps can pick any of them
+    #[serde(rename = "engine", default)]
+    engines: Vec<EngineProfile>,
+    // empty uses the system proxy settings
+    #[serde(default = "Default::default")]
+    proxy: String,
+    #[serde(default = "Default::default")]
+    proxy_username: String,
+    // kept as plain text, same as the rest of the file
+    #[serde(default = "Default::default")]
+    proxy_password: String,
+    // 0 waits forever
+    #[serde(default = "default_connect_timeout_secs")]
+    connect_timeout_secs: u32,
+    #[serde(alias = "request_timeout_secs", default = "default_read_timeout_secs")]
+    read_timeout_secs: u32,
+    #[serde(default = "default_retries")]
+    retries: u32,
+}
+
+impl Configuration {
+    pub fn new() -> Self {
+        let mut file_path = config_dir().expect("No config dir found");
+        file_path.push("QInjector");
+        file_path.push(CONFIG_FILE_NAME);
+        let mut config: Self = read_or_initialize(file_path, "config");
+        trace!("Config object: {:?}", config);
+        let mut locale_resources_dir = std::env::current_dir().expect("No current dir found");
+        locale_resources_dir.push("resources");
+        locale_resources_dir.push("locales");
+        config.locale_resources_dir = locale_resources_dir.clone();
+        let locale = init_locale(
+            locale_resources_dir.clone(),
+            config.current_locale_choice.get_choice(),
+        );
+        trace!("Locale object: {:?}", locale);
+        config.current_locale = locale;
+        config.detect_mission_packs();
+        config
+    }
+
+    /// Sets `hip_installed` and `rogue_installed` from what's in the quake dir.
+    pub fn detect_mission_packs(&mut self) {
+        self.hip_installed = MissionPack::Hipnotic.is_installed(&self.quake_dir);
+        self.rogue_installed = MissionPack::Rogue.is_installed(&self.quake_dir);
+        debug!(
+            "Mission packs: hipnotic {}, rogue {}",
+            self.hip_installed, self.rogue_installed
+        );
+    }
+
+    pub fn is_mission_pack_installed(&self, pack: MissionPack) -> bool {
+        match pack {
+            MissionPack::Hipnotic => self.hip_installed,
+            MissionPack::Rogue => self.rogue_installed,
+        }
+    }
+
+    /// The mission packs `file` needs that aren't in the quake dir.
+    pub fn missing_mission_packs(&self, file: &QuakeFile) -> Vec<MissionPack> {
+        required_mission_packs(file)
+            .into_iter()
+            .filter(|pack| !self.is_mission_pack_installed(*pack))
+            .collect()
+    }
+
+    pub fn write_to_file(&self) {
+        let mut file_path = self.config_dir.clone();
+        file_path.push(CONFIG_FILE_NAME);
+        write_to_file(file_path, self, "config");
+    }
+
+    pub fn set_language(&mut self, language: String) {
+        info!("Changing language to {}", language);
+        self.current_locale_choice = Nester::Other(language);
+        let locale = init_locale(
+            self.locale_resources_dir.clone(),
+            self.current_locale_choice.get_choice(),
+        );
+        self.current_locale = locale;
+    }
+
+    pub fn get_date_format(&self) -> String {
+        String::from(match self.date_format.as_str() {
+            "mm-dd-yyyy" => "%m-%d-%Y",
+            "mm.dd.yyyy" => "%m.%d.%Y",
+            "dd-mm-yyyy" => "%d-%m-%Y",
+            "dd.mm.yyyy" => "%d.%m.%Y",
+            _ => {
+                error!("Invalid format provided. Defaulting to USA style");
+                "%m-%d-%Y"
+            }
+        })
+    }
+
+    /// Every engine a map can be played with, the default one built from `quake_exe` first.
+    pub fn engine_profiles(&self) -> Vec<EngineProfile> {
+        let mut profiles = vec![EngineProfile::new(DEFAULT_ENGINE_NAME, &self.quake_exe)];
+        profiles.extend(self.engines.iter().cloned());
+        profiles
+    }
+
+    /// The profile called `name` ready to launch, with its quake dir filled in when it doesn't
+    /// have its own. Unknown names get the default engine.
+    pub fn get_engine(&self, name: &str) -> EngineProfile {
+        let mut profiles = self.engine_profiles();
+        let index = match profiles.iter().position(|profile| profile.name == name) {
+            Some(index) => index,
+            None => {
+                if !name.is_empty() {
+                    warn!("No engine named '{}', using the default one", name);
+                }
+                0
+            }
+        };
+        let mut profile = profiles.swap_remove(index);
+        if profile.quake_dir.is_empty() {
+            profile.quake_dir = self.quake_dir.clone();
+        }
+        profile
+    }
+
+    pub fn network_settings(&self) -> NetworkSettings {
+        NetworkSettings {
+            proxy: self.proxy.clone(),
+            proxy_username: self.proxy_username.clone(),
+            proxy_password: self.proxy_password.clone(),
+            connect_timeout_secs: self.connect_timeout_secs,
+            read_timeout_secs: self.read_timeout_secs,
+            retries: self.retries,
+        }
+    }
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        let mut config_dir = config_dir().expect("No config dir found");
+        config_dir.push("QInjector");
+        let mut image_cache_dir = config_dir.clone();
+        image_cache_dir.push("images");
+        let mut logs_dir = config_dir.clone();
+        logs_dir.push("logs");
+        let mut resource_dir = std::env::current_dir().expect("No current dir found");
+        resource_dir.push("resources");
+        let mut image_resources_dir = resource_dir.clone();
+        image_resources_dir.push("images");
+        let mut locale_resources_dir = resource_dir.clone();
+        locale_resources_dir.push("locales");
+        let locale = init_locale(locale_resources_dir.clone(), LocaleChoice::EnUs.get_name());
+        // real tired of selecting these through the GUI haha
+        let quake_dir = match cfg!(debug_assertions) {
+            true => "/home/samuwen/.steam/debian-installation/steamapps/common/Quake".to_string(),
+            false => Default::default(),
+        };
+        let quake_exe = match cfg!(debug_assertions) {
+            true => "/usr/games/quakespasm".to_string(),
+            false => Default::default(),
+        };
+        let download_dir = match cfg!(debug_assertions) {
+            true => "/home/samuwen/Downloads".to_string(),
+            false => Default::default(),
+        };
+        Self {
+            quake_dir,
+            quake_exe,
+            download_dir,
+            rogue_installed: Default::default(),
+            hip_installed: Default::default(),
+            is_offline: Default::default(),
+            config_dir,
+            image_cache_dir,
+            image_resources_dir,
+            logs_dir,
+            current_locale_choice: Nester::NestedEnum(LocaleChoice::EnUs),
+            current_locale: locale,
+            locale_resources_dir,
+            date_format: String::from("mm-dd-yyyy"),
+            max_concurrent_downloads: default_max_concurrent_downloads(),
+            database_max_age_hours: default_database_max_age_hours(),
+            mirrors: default_mirrors(),
+            launch_options: Default::default(),
+            engines: vec![],
+            proxy: Default::default(),
+            proxy_username: Default::default(),
+            proxy_password: Default::default(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            read_timeout_secs: default_read_timeout_secs(),
+            retries: default_retries(),
+        }
+    }
+}
+
+fn default_max_concurrent_downloads() -> u8 {
+    2
+}
+
+fn default_database_max_age_hours() -> u32 {
+    24
+}
+
+fn default_connect_timeout_secs() -> u32 {
+    15
+}
+
+fn default_read_timeout_secs() -> u32 {
+    30
+}
+
+fn default_retries() -> u32 {
+    2
+}
+
+pub fn default_mirrors() -> Vec<Mirror> {
+    vec![Mirror::from_base_url(
+        "quaddicted",
+        "https://www.quaddicted.com",
+    )]
+}
+
+fn default_game_flag() -> String {
+    String::from("-game")
+}
+
+fn default_uses_basedir() -> bool {
+    true
+}
+
+/// A Quake engine and how it wants to be started.
+#[derive(Clone, Debug, Deserialize, Getters, PartialEq, Serialize, Setters)]
+#[getset(get = "pub", set = "pub")]
+pub struct EngineProfile {
+    name: String,
+    quake_exe: String,
+    // empty means the main quake dir
+    #[serde(default)]
+    quake_dir: String,
+    // go before the user's own options, so those can still override them
+    #[serde(default)]
+    launch_options: String,
+    // the flag the engine takes for a mod dir
+    #[serde(default = "default_game_flag")]
+    game_flag: String,
+    // some engines don't know -basedir and only look in the dir they're started from
+    #[serde(default = "default_uses_basedir")]
+    uses_basedir: bool,
+}
+
+impl EngineProfile {
+    pub fn new(name: &str, quake_exe: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            quake_exe: quake_exe.to_owned(),
+            quake_dir: String::new(),
+            launch_options: String::new(),
+            game_flag: default_game_flag(),
+            uses_basedir: default_uses_basedir(),
+        }
+    }
+}
+
+/// Where to find the database, map zips and screenshots. `{id}` in the map and screenshot urls
+/// is swapped for the map id.
+#[derive(Clone, Debug, Default, Deserialize, Getters, Serialize, Setters)]
+#[getset(get = "pub", set = "pub")]
+pub struct Mirror {
+    name: String,
+    database_url: String,
+    map_url: String,
+    screenshot_url: String,
+}
+
+impl Mirror {
+    pub fn new(name: &str, database_url: &str, map_url: &str, screenshot_url: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            database_url: database_url.to_owned(),
+            map_url: map_url.to_owned(),
+            screenshot_url: screenshot_url.to_owned(),
+        }
+    }
+
+    /// A mirror laid out the same way as quaddicted.com under `base_url`.
+    pub fn from_base_url(name: &str, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Self::new(
+            name,
+            &format!("{}/reviews/quaddicted_database.xml", base_url),
+            &format!("{}/filebase/{{id}}.zip", base_url),
+            &format!("{}/reviews/screenshots/{{id}}.jpg", base_url),
+        )
+    }
+
+    pub fn get_map_url(&self, map_id: &str) -> String {
+        self.map_url.replace("{id}", map_id)
+    }
+
+    pub fn get_screenshot_url(&self, map_id: &str) -> String {
+        self.screenshot_url.replace("{id}", map_id)
+    }
+}
+
+/// What the server told us about the database the last time we asked for it.
+#[derive(Clone, Debug, Default, Deserialize, Getters, Serialize, Setters)]
+#[getset(get = "pub", set = "pub")]
+pub struct DatabaseMeta {
+    #[serde(default)]
+    etag: String,
+    #[serde(default)]
+    last_modified: String,
+    // unix seconds, updated even when the server had nothing new
+    #[serde(default)]
+    fetched_at: i64,
+}
+
+impl DatabaseMeta {
+    pub fn new(config_dir: PathBuf) -> Self {
+        let mut file_path = config_dir;
+        file_path.push(DATABASE_META_FILE_NAME);
+        read_or_initialize(file_path, "database meta")
+    }
+
+    pub fn is_stale(&self, max_age_hours: u32) -> bool {
+        let age = Utc::now().timestamp() - self.fetched_at;
+        max_age_hours > 0 && age >= max_age_hours as i64 * 3600
+    }
+
+    pub fn write_to_file(&self, config_dir: PathBuf) {
+        let mut file_path = config_dir;
+        file_path.push(DATABASE_META_FILE_NAME);
+        write_to_file(file_path, self, "database meta");
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters, Serialize, Setters)]
+#[getset(get = "pub", set = "pub")]
+pub struct LocalMaps {
+    #[serde(rename = "map", default)]
+    maps: Vec<MapPack>,
+}
+
+impl LocalMaps {
+    pub fn new(config_dir: PathBuf) -> Self {
+        let mut file_path = config_dir;
+        file_path.push(LOCAL_MAPS_FILE_NAME);
+        debug!("Local file path: {:?}", file_path);
+        let maps = read_or_initialize(file_path, "local maps");
+        trace!("{:?}", maps);
+        maps
+    }
+
+    /// A pack that's already in the list is left as it is, two installs can share a
+    /// dependency.
+    pub fn add_map(&mut self, pack: MapPack) {
+        if self.is_map_installed(&pack.id) {
+            debug!("{} is already in the local list", pack.id);
+            return;
+        }
+        self.maps.push(pack);
+    }
+
+    pub fn remove_map(&mut self, id: &String) {
+        let map_pos = match self.maps.iter().position(|map| &map.id == id) {
+            Some(m) => m,
+            None => {
+                error!("Couldn't find map '{}' to remove from local list", id);
+                return;
+            }
+        };
+        self.maps.remove(map_pos);
+    }
+
+    /// Swaps in a freshly installed copy of a map, keeping what the user set on the old one.
+    pub fn replace_map(&mut self, mut pack: MapPack) {
+        if let Some(old) = self.get_map(&pack.id) {
+            pack.launch_options = old.launch_options.clone();
+            pack.engine = old.engine.clone();
+        }
+        self.remove_map(&pack.id.clone());
+        self.add_map(pack);
+    }
+
+    pub fn get_map(&self, id: &String) -> Option<&MapPack> {
+        self.maps.iter().find(|map| map.id() == id)
+    }
+
+    pub fn is_map_installed(&self, id: &String) -> bool {
+        self.maps.iter().any(|map| map.id() == id)
+    }
+
+    pub fn set_launch_options(&mut self, id: &String, options: String) {
+        match self.maps.iter_mut().find(|map| &map.id == id) {
+            Some(map) => map.launch_options = options,
+            None => error!("Couldn't find map '{}' to set launch options on", id),
+        }
+    }
+
+    pub fn set_engine(&mut self, id: &String, engine: String) {
+        match self.maps.iter_mut().find(|map| &map.id == id) {
+            Some(map) => map.engine = engine,
+            None => error!("Couldn't find map '{}' to set the engine on", id),
+        }
+    }
+
+    pub fn write_to_file(&self, config_dir: PathBuf) {
+        let mut file_path = config_dir;
+        file_path.push(LOCAL_MAPS_FILE_NAME);
+        write_to_file(file_path, self, "local maps");
+    }
+}
+
+#[derive(Builder, Clone, Debug, Default, Deserialize, Getters, Setters, Serialize)]
+#[getset(get = "pub", set = "pub")]
+pub struct MapPack {
+    id: String,
+    #[serde(rename = "file", default)]
+    files: Vec<FileInfo>,
+    // true when this pack was only pulled in to satisfy another pack's requirements
+    #[serde(default)]
+    #[builder(default)]
+    is_dependency: bool,
+    #[serde(rename = "dependency", default)]
+    #[builder(default)]
+    dependencies: Vec<Dependency>,
+    // the file or url a custom install came from, empty for maps from the database
+    #[serde(default)]
+    #[builder(default)]
+    source: String,
+    #[serde(default)]
+    #[builder(default)]
+    title: String,
+    #[serde(default)]
+    #[builder(default)]
+    date: String,
+    // added after the global launch options whenever this map is played
+    #[serde(default)]
+    #[builder(default)]
+    launch_options: String,
+    // the engine profile this map is played with, empty for the default one
+    #[serde(default)]
+    #[builder(default)]
+    engine: String,
+}
+
+impl MapPack {
+    pub fn is_custom(&self) -> bool {
+        !self.source.is_empty()
+    }
+}
+
+/// The database as it looked when the user last cleared the what's new list.
+#[derive(Clone, Debug, Default, Deserialize, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct SeenMaps {
+    #[serde(rename = "map", default)]
+    maps: Vec<SeenMap>,
+}
+
+impl SeenMaps {
+    pub fn new(config_dir: PathBuf) -> Self {
+        let mut file_path = config_dir;
+        file_path.push(SEEN_MAPS_FILE_NAME);
+        read_or_initialize(file_path, "seen maps")
+    }
+
+    pub fn mark_seen(&mut self, files: &[QuakeFile]) {
+        self.maps = files
+            .iter()
+            .map(|file| SeenMap {
+                id: file.id().to_owned(),
+                md5: file.md5().to_owned(),
+                date: file.date().to_owned(),
+                rating: file.rating().to_owned(),
+            })
+            .collect();
+    }
+
+    pub fn write_to_file(&self, config_dir: PathBuf) {
+        let mut file_path = config_dir;
+        file_path.push(SEEN_MAPS_FILE_NAME);
+        write_to_file(file_path, self, "seen maps");
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct SeenMap {
+    id: String,
+    md5: String,
+    date: String,
+    rating: String,
+}
+
+#[derive(Builder, Clone, Debug, Default, Deserialize, Getters, Setters, Serialize)]
+#[getset(get = "pub", set = "pub")]
+pub struct FileInfo {
+    crc: u32,
+    name: String,
+}
+
+/// A pack that was installed for this one, written as its own element so it reads back in.
+#[derive(Clone, Debug, Default, Deserialize, Getters, PartialEq, Serialize)]
+#[getset(get = "pub")]
+pub struct Dependency {
+    id: String,
+}
+
+impl Dependency {
+    pub fn new(id: String) -> Self {
+        Self { id }
+    }
+}
+
+fn read_or_initialize<T: DeserializeOwned + Default>(path: impl AsRef<Path>, name: &str) -> T {
+    trace!("Initializing {}", name);
+    let path = path.as_ref();
+    let file_opt = File::open(path);
+    match file_opt {
+        Ok(f) => {
+            info!("{} file found. Starting parse", name);
+            let reader = BufReader::new(f);
+            let deserialized_result = from_reader(reader);
+            match deserialized_result {
+                Ok(c) => {
+                    info!("{} file parsed successfully", name);
+                    c
+                }
+                Err(e) => {
+                    error!("Error reading {} file: {}", name, e);
+                    // the default gets written over it later, so keep what was there
+                    back_up(path, name);
+                    warn!("Generating empty default");
+                    T::default()
+                }
+            }
+        }
+        Err(e) => {
+            info!("{} file not found. Generating default", name);
+            trace!("Error message: {}", e);
+            T::default()
+        }
+    }
+}
+
+fn back_up(path: &Path, name: &str) {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(format!(".{}.bak", Utc::now().format("%Y%m%d%H%M%S")));
+    match rename(path, &backup_path) {
+        Ok(_) => warn!("Moved the unreadable {} file to {:?}", name, backup_path),
+        Err(e) => error!("Couldn't back up the {} file: {}", name, e),
+    }
+}
+
+fn write_to_file<T: Serialize>(path: impl AsRef<Path>, obj: &T, name: &str) {
+    trace!("Writing {} to file", name);
+    let mut file = File::create(path).unwrap();
+    match to_writer(&mut file, obj) {
+        Ok(_) => (),
+        Err(e) => {
+            error!("Failed to serialize {}: {}", name, e);
+            return;
+        }
+    };
+    file.flush()
+        .expect("unable to write file for some dumb reason");
+}
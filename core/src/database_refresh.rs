@@ -0,0 +1,134 @@
+use crate::configuration::{DatabaseMeta, Mirror};
+use crate::download_error::DownloadError;
+use crate::quake_file::{
+    keep_known_good, read_database_file, QuakeFile, BACKUP_FILE_NAME, DATABASE_FILE_NAME,
+};
+use crate::request_utils::{get_database_if_changed, DatabaseFetch};
+use chrono::Utc;
+use getset::Getters;
+use log::*;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, Getters)]
+#[getset(get = "pub")]
+pub struct DatabaseDiff {
+    added: Vec<QuakeFile>,
+    updated: Vec<QuakeFile>,
+    removed: Vec<QuakeFile>,
+}
+
+impl DatabaseDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+pub enum DatabaseRefresh {
+    Unchanged,
+    Updated(DatabaseDiff),
+}
+
+/// Whether the saved database was last fetched longer than `max_age_hours` ago. Without a
+/// database there's nothing to be stale, loading one fetches it anyway.
+pub fn is_database_stale(config_dir: &Path, max_age_hours: u32) -> bool {
+    if !config_dir.join(DATABASE_FILE_NAME).is_file() {
+        return false;
+    }
+    let meta = DatabaseMeta::new(config_dir.to_path_buf());
+    let is_stale = meta.is_stale(max_age_hours);
+    if is_stale {
+        info!("Database is older than {} hours", max_age_hours);
+    }
+    is_stale
+}
+
+/// Asks the server for the database if it changed since the last fetch and reports what's
+/// different about the new one.
+pub fn refresh_database(
+    config_dir: &Path,
+    mirrors: &[Mirror],
+) -> Result<DatabaseRefresh, DownloadError> {
+    info!("Refreshing the database");
+    let file_path = config_dir.join(DATABASE_FILE_NAME);
+    let old_files = read_database_file(&file_path);
+    // the cached headers only mean something while the file they describe is still there
+    let mut meta = match old_files.is_some() {
+        true => DatabaseMeta::new(config_dir.to_path_buf()),
+        false => DatabaseMeta::default(),
+    };
+    let fetch = get_database_if_changed(&file_path, meta.etag(), meta.last_modified(), mirrors)?;
+    meta.set_fetched_at(Utc::now().timestamp());
+    let refresh = match fetch {
+        DatabaseFetch::NotModified => DatabaseRefresh::Unchanged,
+        DatabaseFetch::Updated {
+            etag,
+            last_modified,
+        } => {
+            let new_files = match read_database_file(&file_path) {
+                Some(files) => files,
+                None => {
+                    restore_known_good(config_dir);
+                    let msg = "the server sent a database that can't be read";
+                    return Err(Error::new(ErrorKind::InvalidData, msg).into());
+                }
+            };
+            keep_known_good(config_dir);
+            meta.set_etag(etag);
+            meta.set_last_modified(last_modified);
+            let diff = diff_databases(&old_files.unwrap_or_default(), &new_files);
+            debug!(
+                "Database diff: {} new, {} updated, {} removed",
+                diff.added.len(),
+                diff.updated.len(),
+                diff.removed.len()
+            );
+            DatabaseRefresh::Updated(diff)
+        }
+    };
+    meta.write_to_file(config_dir.to_path_buf());
+    Ok(refresh)
+}
+
+// the meta file wasn't touched, so its headers still describe the backup
+fn restore_known_good(config_dir: &Path) {
+    let file_path = config_dir.join(DATABASE_FILE_NAME);
+    let backup_path = config_dir.join(BACKUP_FILE_NAME);
+    let result = match backup_path.exists() {
+        true => fs::copy(&backup_path, &file_path).map(|_| ()),
+        false => fs::remove_file(&file_path),
+    };
+    if let Err(e) = result {
+        error!("Couldn't put back the last known-good database: {}", e);
+    }
+}
+
+fn diff_databases(old_files: &[QuakeFile], new_files: &[QuakeFile]) -> DatabaseDiff {
+    let old_by_id: HashMap<&String, &QuakeFile> =
+        old_files.iter().map(|file| (file.id(), file)).collect();
+    let new_by_id: HashMap<&String, &QuakeFile> =
+        new_files.iter().map(|file| (file.id(), file)).collect();
+    let mut diff = DatabaseDiff::default();
+    for file in new_files {
+        match old_by_id.get(file.id()) {
+            None => diff.added.push(file.clone()),
+            // a re-release gets a new zip, so a new checksum
+            Some(old)
+                if old.md5() != file.md5()
+                    || old.date() != file.date()
+                    || old.rating() != file.rating() =>
+            {
+                diff.updated.push(file.clone())
+            }
+            Some(_) => (),
+        }
+    }
+    diff.removed = old_files
+        .iter()
+        .filter(|file| !new_by_id.contains_key(file.id()))
+        .cloned()
+        .collect();
+    diff
+}
@@ -0,0 +1,115 @@
+use crate::configuration::LocalMaps;
+use crate::quake_file::QuakeFile;
+use crate::quake_paths::MissionPack;
+use log::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub enum DependencyError {
+    Missing { map_id: String, required_by: String },
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for DependencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyError::Missing {
+                map_id,
+                required_by,
+            } => write!(
+                f,
+                "'{}' requires '{}', which is not in the database",
+                required_by, map_id
+            ),
+            DependencyError::Cycle(ids) => {
+                write!(f, "Circular requirements: {}", ids.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+/// Walks the `requirements` of `map_id` and returns every pack that still has to be installed
+/// before it, in install order. Packs that are already installed locally are not descended into.
+pub fn resolve_dependencies(
+    map_id: &String,
+    files: &[QuakeFile],
+    local_maps: &LocalMaps,
+) -> Result<Vec<QuakeFile>, DependencyError> {
+    trace!("Resolving dependencies for {}", map_id);
+    let files_by_id: HashMap<&String, &QuakeFile> = files.iter().map(|f| (f.id(), f)).collect();
+    let mut resolver = Resolver {
+        files_by_id,
+        local_maps,
+        stack: vec![],
+        visited: HashSet::new(),
+        order: vec![],
+    };
+    resolver.visit(map_id)?;
+    // the requested map is always the last thing visited
+    resolver.order.pop();
+    debug!(
+        "Dependencies for {}: {:?}",
+        map_id,
+        resolver
+            .order
+            .iter()
+            .map(|f| f.id())
+            .collect::<Vec<&String>>()
+    );
+    Ok(resolver.order)
+}
+
+struct Resolver<'a> {
+    files_by_id: HashMap<&'a String, &'a QuakeFile>,
+    local_maps: &'a LocalMaps,
+    stack: Vec<String>,
+    visited: HashSet<String>,
+    order: Vec<QuakeFile>,
+}
+
+impl<'a> Resolver<'a> {
+    fn visit(&mut self, id: &String) -> Result<(), DependencyError> {
+        if self.visited.contains(id) {
+            return Ok(());
+        }
+        if let Some(pos) = self.stack.iter().position(|s| s == id) {
+            let mut cycle = self.stack[pos..].to_vec();
+            cycle.push(id.to_owned());
+            return Err(DependencyError::Cycle(cycle));
+        }
+        // mission packs come with the game, not the database, see `required_mission_packs`
+        if MissionPack::from_id(id).is_some() {
+            trace!("{} is a mission pack", id);
+            self.visited.insert(id.to_owned());
+            return Ok(());
+        }
+        // only the requested map gets installed regardless of local state
+        if !self.stack.is_empty() && self.local_maps.is_map_installed(id) {
+            trace!("{} is already installed", id);
+            self.visited.insert(id.to_owned());
+            return Ok(());
+        }
+        let file = match self.files_by_id.get(id) {
+            Some(f) => *f,
+            None => {
+                return Err(DependencyError::Missing {
+                    map_id: id.to_owned(),
+                    required_by: self.stack.last().cloned().unwrap_or_default(),
+                })
+            }
+        };
+        self.stack.push(id.to_owned());
+        if let Some(requirements) = file.tech_info().requirements() {
+            for req in requirements.req_file() {
+                self.visit(req.id())?;
+            }
+        }
+        self.stack.pop();
+        self.visited.insert(id.to_owned());
+        self.order.push(file.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::io::ErrorKind;
+use zip::result::ZipError;
+
+#[derive(Debug)]
+pub enum DownloadError {
+    Network(reqwest::Error),
+    HttpStatus {
+        url: String,
+        status: u16,
+    },
+    MissingLength(String),
+    Io(std::io::Error),
+    Checksum {
+        file_name: String,
+        expected: String,
+        actual: String,
+    },
+    UnsupportedFormat(String),
+    Cancelled,
+}
+
+impl DownloadError {
+    /// Whether trying the same request again has a chance of working.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            DownloadError::Network(e) => !e.is_builder(),
+            DownloadError::HttpStatus { status, .. } => {
+                *status >= 500 || *status == 408 || *status == 429
+            }
+            DownloadError::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::TimedOut
+                    | ErrorKind::UnexpectedEof
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::Interrupted
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Network(e) => write!(f, "Network error: {}", e),
+            DownloadError::HttpStatus { url, status } => {
+                write!(f, "Server answered {} for {}", status, url)
+            }
+            DownloadError::MissingLength(url) => {
+                write!(f, "Server didn't report a file size for {}", url)
+            }
+            DownloadError::Io(e) => write!(f, "File error: {}", e),
+            DownloadError::Checksum {
+                file_name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for {}: expected {}, got {}",
+                file_name, expected, actual
+            ),
+            DownloadError::UnsupportedFormat(file_name) => {
+                write!(f, "Don't know how to unpack {}", file_name)
+            }
+            DownloadError::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for DownloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DownloadError::Network(e) => Some(e),
+            DownloadError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for DownloadError {
+    fn from(e: reqwest::Error) -> Self {
+        DownloadError::Network(e)
+    }
+}
+
+impl From<std::io::Error> for DownloadError {
+    fn from(e: std::io::Error) -> Self {
+        DownloadError::Io(e)
+    }
+}
+
+// a zip we can't read is as good as a broken file on disk
+impl From<ZipError> for DownloadError {
+    fn from(e: ZipError) -> Self {
+        match e {
+            ZipError::Io(e) => DownloadError::Io(e),
+            e => DownloadError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::download_error::DownloadError;
+use getset::Getters;
+
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct DownloadProgress {
+    percent: f64,
+    is_done: bool,
+    file_name: String,
+    error: Option<String>,
+    // bytes that were already on disk from an earlier attempt
+    resumed_bytes: u64,
+}
+
+impl DownloadProgress {
+    pub fn not_done_dl(percent: f64, file_name: &str) -> Self {
+        Self {
+            percent: percent * 0.95,
+            is_done: false,
+            file_name: file_name.to_owned(),
+            error: None,
+            resumed_bytes: 0,
+        }
+    }
+
+    pub fn not_done_extract(percent: f64, file_name: &str) -> Self {
+        Self {
+            percent: 0.95 + percent * 0.05,
+            is_done: false,
+            file_name: file_name.to_owned(),
+            error: None,
+            resumed_bytes: 0,
+        }
+    }
+
+    pub fn done(file_name: &str) -> Self {
+        Self {
+            percent: 100.0,
+            is_done: true,
+            file_name: file_name.to_owned(),
+            error: None,
+            resumed_bytes: 0,
+        }
+    }
+
+    pub fn failed(file_name: &str, error: &DownloadError) -> Self {
+        Self {
+            percent: 0.0,
+            is_done: true,
+            file_name: file_name.to_owned(),
+            error: Some(error.to_string()),
+            resumed_bytes: 0,
+        }
+    }
+
+    pub fn with_resumed_bytes(self, resumed_bytes: u64) -> Self {
+        Self {
+            resumed_bytes,
+            ..self
+        }
+    }
+}
+
+/// Where downloads and installs send their progress. Front ends turn it into whatever they
+/// show, a plain closure does the job for most of them. Downloads run on the shared network
+/// runtime, hence the `Sync`.
+pub trait ProgressReporter: Sync {
+    fn report(&self, progress: DownloadProgress);
+}
+
+impl<F: Fn(DownloadProgress) + Sync> ProgressReporter for F {
+    fn report(&self, progress: DownloadProgress) {
+        self(progress)
+    }
+}
@@ -0,0 +1,148 @@
+use getset::Getters;
+use log::*;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueueState {
+    Queued,
+    Running,
+    Failed(String),
+    Done,
+    Cancelled,
+}
+
+impl QueueState {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, QueueState::Queued | QueueState::Running)
+    }
+}
+
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct QueueItem {
+    map_id: String,
+    // what the install will pull in first, as far as was known when it was queued
+    dependencies: Vec<String>,
+    state: QueueState,
+    // shared with the install thread, which checks it between reads
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl QueueItem {
+    fn new(map_id: &String, dependencies: Vec<String>) -> Self {
+        Self {
+            map_id: map_id.to_owned(),
+            dependencies,
+            state: QueueState::Queued,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct DownloadQueue {
+    items: Vec<QueueItem>,
+    max_concurrent: usize,
+}
+
+impl DownloadQueue {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            items: vec![],
+            max_concurrent: max_concurrent.max(1),
+        }
+    }
+
+    pub fn set_max_concurrent(&mut self, max_concurrent: usize) {
+        self.max_concurrent = max_concurrent.max(1);
+    }
+
+    /// Returns false if the map is already waiting or being installed.
+    pub fn enqueue(&mut self, map_id: &String, dependencies: Vec<String>) -> bool {
+        if let Some(item) = self.get_item(map_id) {
+            if !item.state.is_finished() {
+                debug!("{} is already in the queue", map_id);
+                return false;
+            }
+        }
+        self.remove(map_id);
+        info!("Queueing {}", map_id);
+        self.items.push(QueueItem::new(map_id, dependencies));
+        true
+    }
+
+    /// Marks the next waiting item as running, if there is a free slot for it. Items that
+    /// share a map with a running install wait for it, so nothing is downloaded twice.
+    pub fn start_next(&mut self) -> Option<QueueItem> {
+        let running = self
+            .items
+            .iter()
+            .filter(|item| item.state == QueueState::Running)
+            .count();
+        if running >= self.max_concurrent {
+            return None;
+        }
+        let in_flight = self.in_flight();
+        let item = self.items.iter_mut().find(|item| {
+            item.state == QueueState::Queued
+                && !in_flight.contains(&item.map_id)
+                && !item.dependencies.iter().any(|id| in_flight.contains(id))
+        })?;
+        item.state = QueueState::Running;
+        Some(item.clone())
+    }
+
+    pub fn finish(&mut self, map_id: &String, state: QueueState) {
+        if let Some(item) = self.get_item_mut(map_id) {
+            debug!("{} finished as {:?}", map_id, state);
+            item.state = state;
+        }
+    }
+
+    /// Waiting items are cancelled straight away, running ones once their thread notices.
+    pub fn cancel(&mut self, map_id: &String) {
+        if let Some(item) = self.get_item_mut(map_id) {
+            match item.state {
+                QueueState::Queued => item.state = QueueState::Cancelled,
+                QueueState::Running => item.cancel_flag.store(true, Ordering::Relaxed),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn retry(&mut self, map_id: &String) -> bool {
+        match self.get_item_mut(map_id) {
+            Some(item) if matches!(item.state, QueueState::Failed(_) | QueueState::Cancelled) => {
+                info!("Retrying {}", map_id);
+                *item = QueueItem::new(map_id, item.dependencies.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove(&mut self, map_id: &String) {
+        self.items.retain(|item| &item.map_id != map_id);
+    }
+
+    pub fn get_item(&self, map_id: &String) -> Option<&QueueItem> {
+        self.items.iter().find(|item| &item.map_id == map_id)
+    }
+
+    fn get_item_mut(&mut self, map_id: &String) -> Option<&mut QueueItem> {
+        self.items.iter_mut().find(|item| &item.map_id == map_id)
+    }
+
+    // every map a running install is fetching, dependencies included
+    fn in_flight(&self) -> HashSet<String> {
+        self.items
+            .iter()
+            .filter(|item| item.state == QueueState::Running)
+            .flat_map(|item| item.dependencies.iter().chain(Some(&item.map_id)))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,189 @@
+use crate::launch_args::join_args;
+use chrono::Local;
+use derive_builder::Builder;
+use getset::Getters;
+use log::*;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a running engine has to say, one line at a time.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameOutput {
+    Stdout(String),
+    Stderr(String),
+    // None when the engine didn't exit on its own, like when it was killed
+    Exited(Option<i32>),
+}
+
+#[derive(Builder, Clone, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct GamePlayer {
+    quake_exe: String,
+    // engines that don't take -basedir find the game files from here
+    working_dir: String,
+    map_id: String,
+    // built by `LaunchArgs`
+    args: Vec<String>,
+}
+
+impl GamePlayer {
+    /// Starts the engine without waiting for it. Everything it prints goes to `on_output` as
+    /// it comes, from threads of its own, and into the log at `log_path` when there is one.
+    pub fn launch<F>(&self, log_path: Option<&Path>, on_output: F) -> io::Result<RunningGame>
+    where
+        F: Fn(GameOutput) + Send + Sync + 'static,
+    {
+        info!("Attempting to play game: {}", self.map_id);
+        let command_line = self.command_line();
+        debug!("{}", command_line);
+        // set up before the engine starts, nothing after the spawn may fail and leave it
+        // running with nobody reading its output
+        let log = match log_path {
+            Some(path) => match File::create(path) {
+                Ok(mut file) => {
+                    if let Err(e) = writeln!(file, "{}", command_line) {
+                        warn!("Couldn't write to game log {:?}: {}", path, e);
+                    }
+                    Some(file)
+                }
+                Err(e) => {
+                    warn!("Couldn't create game log {:?}: {}", path, e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let mut cmd = Command::new(&self.quake_exe);
+        if !self.working_dir.is_empty() {
+            cmd.current_dir(&self.working_dir);
+        }
+        let mut child = cmd
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let log = Arc::new(Mutex::new(log));
+        let on_output = Arc::new(on_output);
+        let stdout = child.stdout.take().expect("Child has no stdout");
+        let stderr = child.stderr.take().expect("Child has no stderr");
+        let readers = vec![
+            spawn_reader(stdout, GameOutput::Stdout, &log, &on_output)?,
+            spawn_reader(stderr, GameOutput::Stderr, &log, &on_output)?,
+        ];
+        let child = Arc::new(Mutex::new(child));
+        let wait_child = child.clone();
+        let waiter = thread::Builder::new()
+            .name(format!("game-{}", self.map_id))
+            .spawn(move || {
+                for reader in readers {
+                    if reader.join().is_err() {
+                        error!("Game output reader panicked");
+                    }
+                }
+                let code = wait_for_exit(&wait_child);
+                info!("Game exited with {:?}", code);
+                if let Some(file) = log.lock().unwrap().as_mut() {
+                    let _ = writeln!(file, "exit code: {:?}", code);
+                }
+                on_output(GameOutput::Exited(code));
+                code
+            })?;
+        Ok(RunningGame { child, waiter })
+    }
+
+    /// The whole launch as one line, quoted so it could be pasted into a shell.
+    pub fn command_line(&self) -> String {
+        let mut argv = vec![self.quake_exe.clone()];
+        argv.extend(self.args.iter().cloned());
+        join_args(&argv)
+    }
+}
+
+/// A game started by `GamePlayer::launch`.
+pub struct RunningGame {
+    child: Arc<Mutex<Child>>,
+    waiter: JoinHandle<Option<i32>>,
+}
+
+impl RunningGame {
+    pub fn kill(&self) {
+        info!("Killing the game");
+        if let Err(e) = self.child.lock().unwrap().kill() {
+            // most likely it already exited
+            warn!("Couldn't kill the game: {}", e);
+        }
+    }
+
+    /// Blocks until the engine exits and every line it printed was handed out.
+    pub fn wait(self) -> Option<i32> {
+        self.waiter.join().unwrap_or(None)
+    }
+}
+
+/// A new log file in `logs_dir` for one play session of `map_id`.
+pub fn session_log_path(logs_dir: &Path, map_id: &str) -> PathBuf {
+    let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+    logs_dir.join(format!("game-{}-{}.log", map_id, timestamp))
+}
+
+// polls instead of blocking in wait so a kill can always get at the child
+fn wait_for_exit(child: &Mutex<Child>) -> Option<i32> {
+    loop {
+        match child.lock().unwrap().try_wait() {
+            Ok(Some(status)) => return status.code(),
+            Ok(None) => (),
+            Err(e) => {
+                error!("Couldn't wait on the game: {}", e);
+                return None;
+            }
+        }
+        thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+fn spawn_reader<R, F>(
+    pipe: R,
+    to_output: fn(String) -> GameOutput,
+    log: &Arc<Mutex<Option<File>>>,
+    on_output: &Arc<F>,
+) -> io::Result<JoinHandle<()>>
+where
+    R: Read + Send + 'static,
+    F: Fn(GameOutput) + Send + Sync + 'static,
+{
+    let log = log.clone();
+    let on_output = on_output.clone();
+    thread::Builder::new()
+        .name("game-output".to_string())
+        .spawn(move || {
+            let mut reader = BufReader::new(pipe);
+            let mut bytes = vec![];
+            loop {
+                bytes.clear();
+                match reader.read_until(b'\n', &mut bytes) {
+                    Ok(0) => break,
+                    Ok(_) => (),
+                    Err(e) => {
+                        warn!("Stopped reading game output: {}", e);
+                        break;
+                    }
+                }
+                // engines print whatever encoding they like
+                let line = String::from_utf8_lossy(&bytes)
+                    .trim_end_matches(&['\r', '\n'][..])
+                    .to_string();
+                if let Some(file) = log.lock().unwrap().as_mut() {
+                    let _ = writeln!(file, "{}", line);
+                }
+                on_output(to_output(line));
+            }
+        })
+}
@@ -0,0 +1,125 @@
+use log::*;
+use once_cell::sync::Lazy;
+use reqwest::{Client, Proxy};
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+pub const USER_AGENT: &str = concat!("rq_injector/", env!("CARGO_PKG_VERSION"));
+
+/// The parts of the configuration that shape every request.
+#[derive(Clone, Default, PartialEq)]
+pub struct NetworkSettings {
+    // empty means whatever the environment says
+    pub proxy: String,
+    pub proxy_username: String,
+    pub proxy_password: String,
+    // 0 waits forever
+    pub connect_timeout_secs: u32,
+    pub read_timeout_secs: u32,
+    // extra attempts per mirror before moving on to the next one
+    pub retries: u32,
+}
+
+impl NetworkSettings {
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        as_timeout(self.connect_timeout_secs)
+    }
+
+    pub fn read_timeout(&self) -> Option<Duration> {
+        as_timeout(self.read_timeout_secs)
+    }
+}
+
+fn as_timeout(secs: u32) -> Option<Duration> {
+    match secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs as u64)),
+    }
+}
+
+struct SharedClient {
+    client: Client,
+    settings: NetworkSettings,
+}
+
+// a couple of workers is plenty, the requests spend their time waiting on the network
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("net")
+        .enable_all()
+        .build()
+        .expect("Failed to start the network runtime")
+});
+
+static CLIENT: Lazy<RwLock<SharedClient>> = Lazy::new(|| {
+    let settings = NetworkSettings::default();
+    let client = build_client(&settings).expect("Failed to build the default http client");
+    RwLock::new(SharedClient { client, settings })
+});
+
+/// Swaps in a client for new settings. Requests already running finish on the old one.
+pub fn configure(settings: &NetworkSettings) {
+    if &CLIENT.read().unwrap().settings == settings {
+        return;
+    }
+    // not the whole struct, the password has no business in the logs
+    info!(
+        "Configuring http client: proxy '{}', connect timeout {}s, read timeout {}s, {} retries",
+        settings.proxy, settings.connect_timeout_secs, settings.read_timeout_secs, settings.retries
+    );
+    match build_client(settings) {
+        Ok(client) => {
+            *CLIENT.write().unwrap() = SharedClient {
+                client,
+                settings: settings.clone(),
+            }
+        }
+        Err(e) => error!(
+            "Couldn't apply network settings, keeping the old ones: {}",
+            e
+        ),
+    }
+}
+
+/// Cheap to call, clones share one connection pool.
+pub fn client() -> Client {
+    CLIENT.read().unwrap().client.clone()
+}
+
+/// How long a download may go without receiving anything before it's given up on.
+pub fn read_timeout() -> Option<Duration> {
+    CLIENT.read().unwrap().settings.read_timeout()
+}
+
+pub fn retries() -> u32 {
+    CLIENT.read().unwrap().settings.retries
+}
+
+pub fn runtime() -> &'static Runtime {
+    &RUNTIME
+}
+
+/// Runs a request to completion on the calling thread. Only for threads that aren't part of
+/// the runtime, like install workers and the command line.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    RUNTIME.block_on(future)
+}
+
+fn build_client(settings: &NetworkSettings) -> Result<Client, reqwest::Error> {
+    let mut builder = Client::builder().user_agent(USER_AGENT);
+    // only the connect gets a hard limit, a big zip can take as long as it needs
+    if let Some(timeout) = settings.connect_timeout() {
+        builder = builder.connect_timeout(timeout);
+    }
+    if !settings.proxy.is_empty() {
+        let mut proxy = Proxy::all(&settings.proxy)?;
+        if !settings.proxy_username.is_empty() {
+            proxy = proxy.basic_auth(&settings.proxy_username, &settings.proxy_password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    builder.build()
+}
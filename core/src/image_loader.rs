@@ -0,0 +1,62 @@
+use crate::configuration::Mirror;
+use crate::http::block_on;
+use crate::request_utils::fetch_image;
+use getset::Getters;
+use log::*;
+use std::fs::File;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, Getters)]
+#[getset(get = "pub")]
+pub struct ImageLoader {
+    path: PathBuf,
+    map_id: String,
+    path_string: String,
+    // shipped with the app, shown when there's no screenshot to be had
+    not_found_image: PathBuf,
+}
+
+impl ImageLoader {
+    pub fn new(map_id: String, path_string: String) -> Self {
+        Self {
+            path: PathBuf::new(),
+            path_string,
+            map_id,
+            not_found_image: PathBuf::new(),
+        }
+    }
+
+    pub fn with_not_found_image(self, not_found_image: PathBuf) -> Self {
+        Self {
+            not_found_image,
+            ..self
+        }
+    }
+
+    pub fn load_map_image(&mut self, is_offline: bool, image_path: PathBuf, mirrors: &[Mirror]) {
+        block_on(self.fetch_map_image(is_offline, image_path, mirrors))
+    }
+
+    /// Same as `load_map_image`, but waits on the download without tying up a thread.
+    pub async fn fetch_map_image(
+        &mut self,
+        is_offline: bool,
+        image_path: PathBuf,
+        mirrors: &[Mirror],
+    ) {
+        let mut path = image_path.clone();
+        path.push(format!("{}.jpg", self.map_id));
+        self.path = path;
+        info!("Attempting to load image at: {:?}", self.path);
+        if File::open(&self.path).is_err() {
+            // gotta get from remote
+            if is_offline {
+                debug!("We're offline, set path to not found image");
+                self.path = self.not_found_image.clone();
+            } else if let Err(e) = fetch_image(&self.map_id, &self.path, mirrors).await {
+                warn!("Couldn't get image for {}: {}", self.map_id, e);
+                self.path = self.not_found_image.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use dirs::home_dir;
+use getset::Getters;
+use log::*;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+// the usual names of source ports, most likely first
+const ENGINE_NAMES: [&str; 11] = [
+    "ironwail",
+    "quakespasm",
+    "quakespasm-spiked",
+    "vkquake",
+    "fteqw",
+    "fteqw64",
+    "darkplaces",
+    "darkplaces-sdl",
+    "joequake",
+    "quake",
+    "quake_x64_steam",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InstallSource {
+    Steam,
+    Gog,
+    Heroic,
+    System,
+}
+
+impl fmt::Display for InstallSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InstallSource::Steam => "Steam",
+            InstallSource::Gog => "GOG",
+            InstallSource::Heroic => "Heroic",
+            InstallSource::System => "System",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A Quake directory that has the game data in it, paired with an engine that could run it.
+#[derive(Clone, Debug, Getters, PartialEq)]
+#[getset(get = "pub")]
+pub struct DetectedInstall {
+    quake_dir: PathBuf,
+    // None when no engine turned up, the user still has to pick one
+    quake_exe: Option<PathBuf>,
+    source: InstallSource,
+}
+
+/// Where to look. `from_env` knows the usual places, tests build their own.
+#[derive(Clone, Debug, Default)]
+pub struct SearchPaths {
+    // each holds a steamapps/libraryfolders.vdf
+    pub steam_roots: Vec<PathBuf>,
+    pub gog_dirs: Vec<PathBuf>,
+    // Heroic's gog_store/installed.json files
+    pub heroic_manifests: Vec<PathBuf>,
+    pub heroic_dirs: Vec<PathBuf>,
+    pub system_dirs: Vec<PathBuf>,
+    // searched for engines, like $PATH
+    pub path_dirs: Vec<PathBuf>,
+}
+
+impl SearchPaths {
+    pub fn from_env() -> Self {
+        let home = home_dir().unwrap_or_default();
+        let mut search = SearchPaths {
+            steam_roots: vec![
+                home.join(".steam/steam"),
+                home.join(".local/share/Steam"),
+                home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+                home.join("Library/Application Support/Steam"),
+            ],
+            gog_dirs: vec![home.join("GOG Games/Quake")],
+            heroic_manifests: vec![
+                home.join(".config/heroic/gog_store/installed.json"),
+                home.join(
+                    ".var/app/com.heroicgameslauncher.hgl/config/heroic/gog_store/installed.json",
+                ),
+            ],
+            heroic_dirs: vec![home.join("Games/Heroic/Quake")],
+            system_dirs: vec![
+                PathBuf::from("/usr/games/quake"),
+                PathBuf::from("/usr/local/games/quake"),
+                PathBuf::from("/usr/share/games/quake"),
+                PathBuf::from("/usr/share/quake"),
+                home.join(".quake"),
+            ],
+            path_dirs: match env::var_os("PATH") {
+                Some(path) => env::split_paths(&path).collect(),
+                None => vec![],
+            },
+        };
+        for var in &["ProgramFiles(x86)", "ProgramFiles"] {
+            if let Some(program_files) = env::var_os(var) {
+                let program_files = PathBuf::from(program_files);
+                search.steam_roots.push(program_files.join("Steam"));
+                search
+                    .gog_dirs
+                    .push(program_files.join("GOG Galaxy/Games/Quake"));
+            }
+        }
+        if cfg!(windows) {
+            search.gog_dirs.push(PathBuf::from("C:/GOG Games/Quake"));
+        }
+        search
+    }
+}
+
+/// Every Quake directory found in `search`, once per engine that could play it. Directories
+/// without `id1/pak0.pak` are skipped, there's nothing to play there.
+pub fn detect_installs(search: &SearchPaths) -> Vec<DetectedInstall> {
+    let mut candidates = vec![];
+    for root in &search.steam_roots {
+        for library in steam_libraries(root) {
+            let quake_dir = library.join("steamapps/common/Quake");
+            // the rerelease keeps its own id1 next to the original one
+            candidates.push((quake_dir.join("rerelease"), InstallSource::Steam));
+            candidates.push((quake_dir, InstallSource::Steam));
+        }
+    }
+    for dir in &search.gog_dirs {
+        candidates.push((dir.to_owned(), InstallSource::Gog));
+    }
+    for manifest in &search.heroic_manifests {
+        for dir in heroic_install_paths(manifest) {
+            candidates.push((dir, InstallSource::Heroic));
+        }
+    }
+    for dir in &search.heroic_dirs {
+        candidates.push((dir.to_owned(), InstallSource::Heroic));
+    }
+    for dir in &search.system_dirs {
+        candidates.push((dir.to_owned(), InstallSource::System));
+    }
+
+    let mut seen_engines = HashSet::new();
+    let path_engines: Vec<PathBuf> = search
+        .path_dirs
+        .iter()
+        .flat_map(|dir| find_engines(dir))
+        // /bin and /usr/bin are often the same place
+        .filter(|engine| {
+            seen_engines.insert(engine.canonicalize().unwrap_or_else(|_| engine.clone()))
+        })
+        .collect();
+    let mut seen = HashSet::new();
+    let mut installs = vec![];
+    for (quake_dir, source) in candidates {
+        if !is_quake_dir(&quake_dir) {
+            continue;
+        }
+        let key = quake_dir
+            .canonicalize()
+            .unwrap_or_else(|_| quake_dir.clone());
+        if !seen.insert(key) {
+            continue;
+        }
+        info!("Found a {} Quake install in {:?}", source, quake_dir);
+        let mut engines = find_engines(&quake_dir);
+        engines.extend(path_engines.iter().cloned());
+        if engines.is_empty() {
+            installs.push(DetectedInstall {
+                quake_dir,
+                quake_exe: None,
+                source,
+            });
+            continue;
+        }
+        for engine in engines {
+            installs.push(DetectedInstall {
+                quake_dir: quake_dir.clone(),
+                quake_exe: Some(engine),
+                source,
+            });
+        }
+    }
+    installs
+}
+
+/// True when `path` has `id1/pak0.pak`, in any case since the DOS era left plenty in capitals.
+pub fn is_quake_dir<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    if path.as_os_str().is_empty() {
+        return false;
+    }
+    match find_entry(path, "id1") {
+        Some(id1) => find_entry(&id1, "pak0.pak").is_some_and(|pak| pak.is_file()),
+        None => false,
+    }
+}
+
+/// The library folders a `libraryfolders.vdf` points at. Handles both the old layout, where
+/// they're numbered keys, and the newer one with a `path` in each entry.
+pub fn parse_library_folders(vdf: &str) -> Vec<PathBuf> {
+    let tokens = vdf_tokens(vdf);
+    let mut libraries = vec![];
+    for pair in tokens.windows(2) {
+        if let [VdfToken::Text(key), VdfToken::Text(value)] = pair {
+            // app ids are numbered too, but their values are sizes rather than paths
+            let is_numbered = !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_digit())
+                && value.contains(&['/', '\\'][..]);
+            if key.eq_ignore_ascii_case("path") || is_numbered {
+                libraries.push(PathBuf::from(value));
+            }
+        }
+    }
+    libraries
+}
+
+fn steam_libraries(root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![root.to_owned()];
+    for vdf_path in &[
+        root.join("steamapps/libraryfolders.vdf"),
+        root.join("config/libraryfolders.vdf"),
+    ] {
+        if let Ok(vdf) = read_to_string(vdf_path) {
+            debug!("Reading Steam libraries from {:?}", vdf_path);
+            libraries.append(&mut parse_library_folders(&vdf));
+        }
+    }
+    libraries
+}
+
+// Heroic's manifest is json, but all we want out of it are the install paths
+fn heroic_install_paths(manifest: &Path) -> Vec<PathBuf> {
+    let json = match read_to_string(manifest) {
+        Ok(json) => json,
+        Err(_) => return vec![],
+    };
+    let tokens = vdf_tokens(&json);
+    tokens
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [VdfToken::Text(key), VdfToken::Text(value)] if key == "install_path" => {
+                Some(PathBuf::from(value))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+enum VdfToken {
+    Text(String),
+    Open,
+    Close,
+}
+
+// quoted strings and braces, everything else is skipped
+fn vdf_tokens(text: &str) -> Vec<VdfToken> {
+    let mut tokens = vec![];
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '[' => tokens.push(VdfToken::Open),
+            '}' | ']' => tokens.push(VdfToken::Close),
+            '"' => {
+                let mut value = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(VdfToken::Text(value));
+            }
+            _ => (),
+        }
+    }
+    tokens
+}
+
+fn find_engines(dir: &Path) -> Vec<PathBuf> {
+    ENGINE_NAMES
+        .iter()
+        .filter_map(|name| {
+            let file_name = format!("{}{}", name, env::consts::EXE_SUFFIX);
+            find_entry(dir, &file_name).filter(|path| path.is_file())
+        })
+        .collect()
+}
+
+// a case insensitive lookup of `name` in `dir`
+pub(crate) fn find_entry(dir: &Path, name: &str) -> Option<PathBuf> {
+    let exact = dir.join(name);
+    if exact.exists() {
+        return Some(exact);
+    }
+    read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+        .map(|entry| entry.path())
+}
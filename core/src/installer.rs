@@ -0,0 +1,1121 @@
+use crate::configuration::*;
+use crate::download_error::DownloadError;
+use crate::download_progress::{DownloadProgress, ProgressReporter};
+use crate::quake_file::QuakeFile;
+use crate::request_utils::{get_file_from_url, get_map_from_remote};
+use crate::safe_extract::{sanitize_entry_name, SafeExtractor};
+use chrono::Local;
+use crc::{crc32, Hasher32};
+use getset::Getters;
+use log::*;
+use sevenz_rust::{Archive, Password, SevenZReader};
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{create_dir_all, read_dir, remove_dir, remove_dir_all, remove_file, File};
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use zip::ZipArchive;
+
+#[derive(Clone, Default, Getters)]
+#[getset(get = "pub")]
+pub struct Installer {
+    download_dir: String,
+    quake_dir: String,
+    path_string: String,
+    map_id: String,
+    md5: String,
+    zip_base_dir: Option<String>,
+    dependencies: Vec<QuakeFile>,
+    installed_map_pack: Option<MapPack>,
+    installed_dependencies: Vec<MapPack>,
+    cancel_flag: Arc<AtomicBool>,
+    mirrors: Vec<Mirror>,
+}
+
+impl Installer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_path_string(self, string: String) -> Self {
+        Self {
+            path_string: string,
+            ..self
+        }
+    }
+
+    pub fn with_download_dir(self, dir: String) -> Self {
+        Self {
+            download_dir: dir,
+            ..self
+        }
+    }
+
+    pub fn with_quake_dir(self, dir: String) -> Self {
+        Self {
+            quake_dir: dir,
+            ..self
+        }
+    }
+
+    pub fn with_map_id(self, id: String) -> Self {
+        Self { map_id: id, ..self }
+    }
+
+    pub fn with_installed_map_pack(self, map_pack: Option<MapPack>) -> Self {
+        Self {
+            installed_map_pack: map_pack,
+            ..self
+        }
+    }
+
+    pub fn with_cancel_flag(self, cancel_flag: Arc<AtomicBool>) -> Self {
+        Self {
+            cancel_flag,
+            ..self
+        }
+    }
+
+    // an empty checksum skips verification
+    pub fn with_md5(self, md5: String) -> Self {
+        Self { md5, ..self }
+    }
+
+    pub fn with_mirrors(self, mirrors: Vec<Mirror>) -> Self {
+        Self { mirrors, ..self }
+    }
+
+    pub fn with_zip_base_dir(self, zip_base_dir: Option<String>) -> Self {
+        Self {
+            zip_base_dir,
+            ..self
+        }
+    }
+
+    // expected to be in install order, see dependencies::resolve_dependencies
+    pub fn with_dependencies(self, dependencies: Vec<QuakeFile>) -> Self {
+        Self {
+            dependencies,
+            ..self
+        }
+    }
+
+    pub fn install_map(&mut self, reporter: &dyn ProgressReporter) -> Result<(), DownloadError> {
+        for dependency in self.dependencies.clone() {
+            info!(
+                "Installing {} as a dependency of {}",
+                dependency.id(),
+                self.map_id
+            );
+            let mut map_pack = self.install_single_map(
+                dependency.id(),
+                dependency.md5(),
+                dependency.tech_info().zip_base_dir(),
+                reporter,
+            )?;
+            map_pack.set_is_dependency(true);
+            self.installed_dependencies.push(map_pack);
+        }
+        let map_id = self.map_id.clone();
+        let mut map_pack =
+            self.install_single_map(&map_id, &self.md5, &self.zip_base_dir, reporter)?;
+        let dependencies = self.dependencies.iter();
+        map_pack.set_dependencies(
+            dependencies
+                .map(|d| Dependency::new(d.id().to_owned()))
+                .collect(),
+        );
+        self.installed_map_pack = Some(map_pack);
+        Ok(())
+    }
+
+    /// Extracts the map again over the top of the current install, fetching the zip if it
+    /// isn't cached anymore.
+    pub fn repair_map(&mut self, reporter: &dyn ProgressReporter) -> Result<(), DownloadError> {
+        info!("Repairing {}", self.map_id);
+        if let Some(old_pack) = self.installed_map_pack.clone().filter(|p| p.is_custom()) {
+            self.install_custom(old_pack.source(), old_pack.title(), reporter)?;
+            if let Some(map_pack) = self.installed_map_pack.as_mut() {
+                map_pack.set_date(old_pack.date().to_owned());
+            }
+            return Ok(());
+        }
+        let map_id = self.map_id.clone();
+        let mut map_pack =
+            self.install_single_map(&map_id, &self.md5, &self.zip_base_dir, reporter)?;
+        if let Some(old_pack) = &self.installed_map_pack {
+            map_pack.set_is_dependency(*old_pack.is_dependency());
+            map_pack.set_dependencies(old_pack.dependencies().clone());
+        }
+        self.installed_map_pack = Some(map_pack);
+        Ok(())
+    }
+
+    /// Installs a map that isn't in the database, from a local archive or any url.
+    pub fn install_custom(
+        &mut self,
+        source: &String,
+        title: &String,
+        reporter: &dyn ProgressReporter,
+    ) -> Result<(), DownloadError> {
+        info!("Installing {} from {}", self.map_id, source);
+        let map_id = self.map_id.clone();
+        let mut map_pack = match self.fetch_and_unpack_custom(&map_id, source, reporter) {
+            Ok(map_pack) => map_pack,
+            Err(e) => {
+                error!("Failed to install {}: {}", map_id, e);
+                reporter.report(DownloadProgress::failed(&map_id, &e));
+                return Err(e);
+            }
+        };
+        map_pack.set_source(source.to_owned());
+        map_pack.set_title(title.to_owned());
+        map_pack.set_date(Local::now().format("%d.%m.%Y").to_string());
+        self.installed_map_pack = Some(map_pack);
+        Ok(())
+    }
+
+    fn fetch_and_unpack_custom(
+        &self,
+        map_id: &String,
+        source: &String,
+        reporter: &dyn ProgressReporter,
+    ) -> Result<MapPack, DownloadError> {
+        let archive_path = match is_url(source) {
+            true => {
+                let file_name = get_url_file_name(source, map_id);
+                let path = Path::new(&self.download_dir).join(file_name);
+                get_file_from_url(source, &path, reporter, &self.cancel_flag)?;
+                path
+            }
+            false => {
+                reporter.report(DownloadProgress::not_done_dl(100.0, map_id));
+                PathBuf::from(source)
+            }
+        };
+        if self.cancel_flag.load(Ordering::Relaxed) {
+            return Err(DownloadError::Cancelled);
+        }
+        let mut archive = open_archive(&archive_path)?;
+        self.unpack_archive_to_dir(archive.as_mut(), map_id, reporter)
+    }
+
+    fn install_single_map(
+        &self,
+        map_id: &String,
+        md5: &String,
+        zip_base_dir: &Option<String>,
+        reporter: &dyn ProgressReporter,
+    ) -> Result<MapPack, DownloadError> {
+        let result = match self.cancel_flag.load(Ordering::Relaxed) {
+            true => Err(DownloadError::Cancelled),
+            false => self.download_and_unpack(map_id, md5, zip_base_dir, reporter),
+        };
+        if let Err(e) = &result {
+            error!("Failed to install {}: {}", map_id, e);
+            reporter.report(DownloadProgress::failed(map_id, e));
+        }
+        result
+    }
+
+    fn download_and_unpack(
+        &self,
+        map_id: &String,
+        md5: &String,
+        zip_base_dir: &Option<String>,
+        reporter: &dyn ProgressReporter,
+    ) -> Result<MapPack, DownloadError> {
+        let start_dl = std::time::Instant::now();
+        trace!("Started installing: {}", map_id);
+        let archive_path = match self.get_local_archive_path(map_id, md5) {
+            Some(path) => {
+                debug!("Using local archive {:?}", path);
+                reporter.report(DownloadProgress::not_done_dl(100.0, map_id));
+                path
+            }
+            None => self.fetch_zip(map_id, md5, reporter)?,
+        };
+        let end_dl = std::time::Instant::now();
+        debug!(
+            "Total download time: {} milliseconds",
+            (end_dl - start_dl).as_millis()
+        );
+        // past this point the install runs to completion so we never leave half a map behind
+        if self.cancel_flag.load(Ordering::Relaxed) {
+            return Err(DownloadError::Cancelled);
+        }
+        let start_unpack = std::time::Instant::now();
+        let mut archive = open_archive(&archive_path)?;
+        let map_pack = match zip_base_dir.as_deref().and_then(parse_base_dir) {
+            Some(base_dir) => {
+                self.unpack_archive_to_base_dir(archive.as_mut(), map_id, &base_dir, reporter)?
+            }
+            None => self.unpack_archive_to_dir(archive.as_mut(), map_id, reporter)?,
+        };
+        let end_unpack = std::time::Instant::now();
+        debug!(
+            "Total unpack time: {} milliseconds",
+            (end_unpack - start_unpack).as_millis()
+        );
+        trace!("Done installing: {}", map_id);
+        Ok(map_pack)
+    }
+
+    fn fetch_zip(
+        &self,
+        map_id: &String,
+        md5: &String,
+        reporter: &dyn ProgressReporter,
+    ) -> Result<PathBuf, DownloadError> {
+        let zip_path = self.get_zip_path(map_id);
+        if self.is_map_zip_downloaded(map_id) {
+            match check_md5(&zip_path, md5) {
+                Ok(_) => {
+                    debug!("Local file found. Stop all the downloading");
+                    reporter.report(DownloadProgress::not_done_dl(100.0, map_id));
+                    return Ok(PathBuf::from(zip_path));
+                }
+                Err(e) => {
+                    warn!("Cached zip is no good, downloading again. {}", e);
+                    remove_file(&zip_path)?;
+                }
+            }
+        }
+        debug!("Zip not found. Grabbing from remote");
+        get_map_from_remote(
+            map_id,
+            &self.download_dir,
+            &self.mirrors,
+            reporter,
+            &self.cancel_flag,
+        )?;
+        if let Err(e) = check_md5(&zip_path, md5) {
+            // one more go in case the transfer got mangled along the way
+            warn!("{}. Retrying download", e);
+            remove_file(&zip_path)?;
+            get_map_from_remote(
+                map_id,
+                &self.download_dir,
+                &self.mirrors,
+                reporter,
+                &self.cancel_flag,
+            )?;
+            if let Err(e) = check_md5(&zip_path, md5) {
+                remove_file(&zip_path)?;
+                return Err(e);
+            }
+        }
+        Ok(PathBuf::from(zip_path))
+    }
+
+    // something other than the database zip the user dropped into the download dir. it has to
+    // be the file the database lists, anything else could be an unrelated release
+    fn get_local_archive_path(&self, map_id: &String, md5: &String) -> Option<PathBuf> {
+        ArchiveKind::ALL
+            .iter()
+            .filter(|kind| **kind != ArchiveKind::Zip)
+            .map(|kind| {
+                Path::new(&self.download_dir).join(format!("{}.{}", map_id, kind.extension()))
+            })
+            .filter(|path| path.is_file())
+            .find(|path| match check_md5(path, md5) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("Not using local archive. {}", e);
+                    false
+                }
+            })
+    }
+
+    /// Removes the files recorded at install time. Files that changed since then, like a
+    /// tweaked config, are left alone unless `purge` is set. Returns whatever was kept.
+    pub fn uninstall_map(&self, purge: bool) -> Vec<String> {
+        trace!("Starting uninstalling: {}", self.map_id);
+        let map_pack = match &self.installed_map_pack {
+            Some(map_pack) if !map_pack.files().is_empty() => map_pack,
+            _ => {
+                // installed before we kept track of files
+                warn!("No file list for {}, removing its directory", self.map_id);
+                self.remove_map_dir();
+                return vec![];
+            }
+        };
+        let mut kept = vec![];
+        let mut touched_dirs = BTreeSet::new();
+        for file_info in map_pack.files() {
+            let path = Path::new(&self.quake_dir).join(file_info.name());
+            match file_crc(&path) {
+                Ok(crc) if crc != *file_info.crc() && !purge => {
+                    info!("Keeping {}, it changed after install", file_info.name());
+                    kept.push(file_info.name().to_owned());
+                    continue;
+                }
+                Ok(_) => (),
+                Err(e) => {
+                    debug!("Skipping {}: {}", file_info.name(), e);
+                    continue;
+                }
+            }
+            match remove_file(&path) {
+                Ok(_) => trace!("Removed file: {:?}", path),
+                Err(e) => {
+                    error!("Failed to remove file: {:?} {}", path, e);
+                    kept.push(file_info.name().to_owned());
+                }
+            }
+            if let Some(parent) = path.parent() {
+                touched_dirs.insert(parent.to_path_buf());
+            }
+        }
+        if purge {
+            // saves and configs the engine wrote into the map's own dir go too
+            self.remove_map_dir();
+        }
+        self.remove_empty_dirs(touched_dirs);
+        trace!("Done uninstalling: {}", self.map_id);
+        kept
+    }
+
+    fn remove_map_dir(&self) {
+        let dir_path = format!("{}/{}", self.quake_dir, self.map_id);
+        if !Path::new(&dir_path).is_dir() {
+            return;
+        }
+        match remove_dir_all(&dir_path) {
+            Ok(_) => info!("Removed dir: {}", dir_path),
+            Err(e) => error!("Failed to remove dir: {} {}", dir_path, e),
+        };
+    }
+
+    fn remove_empty_dirs(&self, dirs: BTreeSet<PathBuf>) {
+        let quake_dir = Path::new(&self.quake_dir);
+        let map_dir = quake_dir.join(&self.map_id);
+        // deepest first so a parent is only looked at once its children are gone
+        for dir in dirs.iter().rev() {
+            let mut current = dir.as_path();
+            while current.starts_with(quake_dir) && current != quake_dir {
+                // shared top level dirs like id1 stay even when we emptied them
+                if current.parent() == Some(quake_dir) && current != map_dir {
+                    break;
+                }
+                let is_empty = match read_dir(current) {
+                    Ok(mut entries) => entries.next().is_none(),
+                    Err(_) => false,
+                };
+                if !is_empty || remove_dir(current).is_err() {
+                    break;
+                }
+                debug!("Removed empty dir: {:?}", current);
+                current = match current.parent() {
+                    Some(parent) => parent,
+                    None => break,
+                };
+            }
+        }
+    }
+
+    fn is_map_zip_downloaded(&self, map_id: &String) -> bool {
+        info!("Checking download dir for file: {:?}", self.download_dir);
+        Path::new(&self.get_zip_path(map_id)).is_file()
+    }
+
+    fn get_zip_path(&self, map_id: &String) -> String {
+        format!("{}/{}.zip", self.download_dir, map_id)
+    }
+
+    /// Extracts into the dir the database says the archive belongs in, relative to the quake
+    /// dir. Entries that already carry (part of) that dir in their path don't get it twice.
+    fn unpack_archive_to_base_dir(
+        &self,
+        archive: &mut dyn MapArchive,
+        map_id: &String,
+        base_dir: &[String],
+        reporter: &dyn ProgressReporter,
+    ) -> Result<MapPack, DownloadError> {
+        let mut root_dir = PathBuf::from(&self.quake_dir);
+        base_dir.iter().for_each(|part| root_dir.push(part));
+        debug!("Extracting {} into {:?}", map_id, root_dir);
+        let files = self.extract_archive(archive, map_id, &root_dir, reporter, |parts| {
+            let skip = base_dir_overlap(base_dir, &parts);
+            parts[skip..].to_vec()
+        })?;
+        reporter.report(DownloadProgress::done(map_id));
+        Ok(MapPackBuilder::default()
+            .id(map_id.to_owned())
+            .files(files)
+            .build()
+            .unwrap())
+    }
+
+    fn unpack_archive_to_dir(
+        &self,
+        archive: &mut dyn MapArchive,
+        map_id: &String,
+        reporter: &dyn ProgressReporter,
+    ) -> Result<MapPack, DownloadError> {
+        let names = archive.entry_names()?;
+        debug!("Archive: {:?}", names);
+        let has_extra_dirs = names
+            .iter()
+            .any(|name| name.contains("/") || name.contains("\\"));
+        let root_dir = Path::new(&self.quake_dir).join(map_id);
+        let mut files = match has_extra_dirs {
+            true => {
+                debug!("We have extra directories, lets create those");
+                self.extract_archive(archive, map_id, &root_dir, reporter, |parts| parts)?
+            }
+            false => {
+                debug!("No extra directories, creating extra dir structure");
+                let mut taken_paks: HashSet<String> = names
+                    .iter()
+                    .map(|name| name.to_ascii_lowercase())
+                    .filter(|name| is_numbered_pak(name))
+                    .collect();
+                self.extract_archive(archive, map_id, &root_dir, reporter, |parts| {
+                    let name = parts[0].to_ascii_lowercase();
+                    if name.ends_with(".pak") && !is_numbered_pak(&name) {
+                        return vec![self.free_pak_name(&root_dir, &mut taken_paks)];
+                    }
+                    match name.contains("bsp") {
+                        true => vec![String::from("maps"), name],
+                        false => vec![name],
+                    }
+                })?
+            }
+        };
+        // only a map that was actually unpacked gets started, a lone pak has its own start
+        let bsp_name = match has_extra_dirs {
+            true => None,
+            false => files
+                .iter()
+                .filter_map(|file| file.name().rsplit('/').next())
+                .find(|name| name.ends_with(".bsp"))
+                .map(|name| name.trim_end_matches(".bsp").to_owned()),
+        };
+        if let Some(bsp_name) = bsp_name {
+            let auto_file_path = root_dir.join("autoexec.cfg");
+            let autoexec = format!("map {}", bsp_name);
+            let mut f = File::create(&auto_file_path)?;
+            f.write_all(autoexec.as_bytes())?;
+            let crc = crc32::checksum_ieee(autoexec.as_bytes());
+            files.push(self.get_file_info(&auto_file_path, crc));
+        }
+        reporter.report(DownloadProgress::done(map_id));
+        Ok(MapPackBuilder::default()
+            .id(map_id.to_owned())
+            .files(files)
+            .build()
+            .unwrap())
+    }
+
+    /// Writes every entry that passes the path checks under `root_dir`. `relocate` gets the
+    /// cleaned up path components and returns where they go relative to the root; anything
+    /// unsafe is skipped rather than failing the whole map.
+    fn extract_archive<F>(
+        &self,
+        archive: &mut dyn MapArchive,
+        map_id: &String,
+        root_dir: &Path,
+        reporter: &dyn ProgressReporter,
+        mut relocate: F,
+    ) -> Result<Vec<FileInfo>, DownloadError>
+    where
+        F: FnMut(Vec<String>) -> Vec<String>,
+    {
+        create_dir_all(root_dir)?;
+        let mut extractor = SafeExtractor::new(
+            root_dir.to_path_buf(),
+            root_dir.starts_with(Path::new(&self.quake_dir).join(map_id)),
+            self.get_owned_files(),
+        );
+        let mut files = vec![];
+        archive.for_each_entry(&mut |entry, reader| {
+            if entry.is_symlink {
+                warn!("Skipping symlink entry: {}", entry.name);
+                return Ok(());
+            }
+            let parts = match sanitize_entry_name(&entry.name) {
+                Ok(parts) => relocate(parts),
+                Err(e) => {
+                    warn!("Skipping entry {}: {}", entry.name, e);
+                    return Ok(());
+                }
+            };
+            if parts.is_empty() {
+                // just the base dir itself
+                return Ok(());
+            }
+            let file_path = match extractor.resolve(&parts, entry.is_dir) {
+                Ok(path) => path,
+                Err(e) => {
+                    warn!("Skipping entry {}: {}", entry.name, e);
+                    return Ok(());
+                }
+            };
+            if entry.is_dir {
+                create_dir_all(&file_path)?;
+                return Ok(());
+            }
+            if let Some(parent) = file_path.parent() {
+                create_dir_all(parent)?;
+            }
+            trace!("Writing out to local file: {:?}", file_path);
+            let crc = write_and_checksum(reader, &file_path)?;
+            files.push(self.get_file_info(&file_path, crc));
+            let fraction = entry.index as f64 / entry.count as f64;
+            reporter.report(DownloadProgress::not_done_extract(fraction, map_id));
+            Ok(())
+        })?;
+        debug!("Extraction went well");
+        Ok(files)
+    }
+
+    // engines only pick up numbered paks, so a loose one gets the first number that isn't
+    // used by someone else
+    fn free_pak_name(&self, root_dir: &Path, taken: &mut HashSet<String>) -> String {
+        let owned_files = self.get_owned_files();
+        let name = (0..)
+            .map(|number| format!("pak{}.pak", number))
+            .find(|name| {
+                let path = root_dir.join(name);
+                !taken.contains(name) && (!path.exists() || owned_files.contains(&path))
+            })
+            .unwrap();
+        taken.insert(name.clone());
+        name
+    }
+
+    // whatever the previous install wrote may be replaced, e.g. when repairing
+    fn get_owned_files(&self) -> HashSet<PathBuf> {
+        match &self.installed_map_pack {
+            Some(map_pack) => map_pack
+                .files()
+                .iter()
+                .map(|file| Path::new(&self.quake_dir).join(file.name()))
+                .collect(),
+            None => HashSet::new(),
+        }
+    }
+
+    // names are stored relative to the quake dir so moving the install doesn't break them
+    fn get_file_info(&self, path: &Path, crc: u32) -> FileInfo {
+        let relative_path = path.strip_prefix(&self.quake_dir).unwrap_or(path);
+        let name = relative_path.to_string_lossy().replace('\\', "/");
+        FileInfoBuilder::default()
+            .name(name)
+            .crc(crc)
+            .build()
+            .unwrap()
+    }
+}
+
+/// Custom maps get their own dir under the quake dir, so the id has to be usable as a plain
+/// folder name and can't take over one of the game's own dirs.
+pub fn is_valid_map_id(map_id: &str) -> bool {
+    let is_reserved = ["id1", "hipnotic", "rogue"]
+        .iter()
+        .any(|dir| dir.eq_ignore_ascii_case(map_id));
+    !map_id.is_empty()
+        && !is_reserved
+        && map_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn is_numbered_pak(name: &str) -> bool {
+    let number = name
+        .strip_prefix("pak")
+        .and_then(|rest| rest.strip_suffix(".pak"));
+    match number {
+        Some(number) => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+fn get_url_file_name(url: &str, map_id: &String) -> String {
+    let path = url.split(&['?', '#'][..]).next().unwrap_or(url);
+    match path.rsplit('/').next() {
+        Some(name) if is_valid_file_name(name) => name.to_owned(),
+        _ => format!("{}.zip", map_id),
+    }
+}
+
+fn is_valid_file_name(name: &str) -> bool {
+    name.contains('.')
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZipStatus {
+    Valid,
+    // doesn't match the database and can't be read either
+    Corrupt(String),
+    // a readable archive that doesn't match the database, usually an older release
+    Stale,
+    Unknown,
+}
+
+// "quoth/", "/id1/maps" and "id1\maps\" all end up as their path components
+fn parse_base_dir(zip_base_dir: &str) -> Option<Vec<String>> {
+    let parts: Vec<String> = zip_base_dir
+        .split(&['/', '\\'][..])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .map(|part| part.to_owned())
+        .collect();
+    match parts.is_empty() || parts.iter().any(|part| part == "..") {
+        true => None,
+        false => Some(parts),
+    }
+}
+
+/// How many leading components of the entry repeat the tail of the base dir, so a
+/// `maps/start.bsp` entry with a base dir of `id1/maps` doesn't end up in `id1/maps/maps`.
+fn base_dir_overlap(base_dir: &[String], entry: &[String]) -> usize {
+    for len in (1..=base_dir.len().min(entry.len())).rev() {
+        let tail = &base_dir[base_dir.len() - len..];
+        let is_match = tail
+            .iter()
+            .zip(&entry[..len])
+            .all(|(a, b)| a.eq_ignore_ascii_case(b));
+        if is_match {
+            return len;
+        }
+    }
+    0
+}
+
+fn is_symlink_entry(unix_mode: Option<u32>) -> bool {
+    match unix_mode {
+        Some(mode) => mode & 0o170000 == 0o120000,
+        None => false,
+    }
+}
+
+fn write_and_checksum<R: Read + ?Sized>(
+    reader: &mut R,
+    path: &Path,
+) -> Result<u32, std::io::Error> {
+    let mut local_file = BufWriter::new(File::create(path)?);
+    let mut digest = crc32::Digest::new(crc32::IEEE);
+    let mut buffer = [0; 0x4000];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        digest.write(&buffer[..read]);
+        local_file.write_all(&buffer[..read])?;
+    }
+    local_file.flush()?;
+    Ok(digest.sum32())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    SevenZip,
+    Rar,
+    Pak,
+    Bsp,
+}
+
+impl ArchiveKind {
+    pub const ALL: [ArchiveKind; 5] = [
+        ArchiveKind::Zip,
+        ArchiveKind::SevenZip,
+        ArchiveKind::Rar,
+        ArchiveKind::Pak,
+        ArchiveKind::Bsp,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveKind::Zip => "zip",
+            ArchiveKind::SevenZip => "7z",
+            ArchiveKind::Rar => "rar",
+            ArchiveKind::Pak => "pak",
+            ArchiveKind::Bsp => "bsp",
+        }
+    }
+
+    /// Goes by the magic bytes first since plenty of releases carry the wrong extension.
+    pub fn detect(path: &Path) -> Option<ArchiveKind> {
+        let mut magic = [0; 8];
+        let read = File::open(path)
+            .and_then(|mut f| f.read(&mut magic))
+            .unwrap_or(0);
+        let magic = &magic[..read];
+        let by_magic = if magic.starts_with(b"PK\x03\x04") {
+            Some(ArchiveKind::Zip)
+        } else if magic.starts_with(b"7z\xBC\xAF\x27\x1C") {
+            Some(ArchiveKind::SevenZip)
+        } else if magic.starts_with(b"Rar!\x1A\x07") {
+            Some(ArchiveKind::Rar)
+        } else if magic.starts_with(b"PACK") {
+            Some(ArchiveKind::Pak)
+        } else if magic.starts_with(&[29, 0, 0, 0]) || magic.starts_with(b"BSP2") {
+            Some(ArchiveKind::Bsp)
+        } else {
+            None
+        };
+        by_magic.or_else(|| {
+            let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+            ArchiveKind::ALL
+                .iter()
+                .find(|kind| kind.extension() == ext)
+                .copied()
+        })
+    }
+}
+
+pub struct ArchiveEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub index: usize,
+    pub count: usize,
+}
+
+type EntryVisitor<'a> = dyn FnMut(&ArchiveEntry, &mut dyn Read) -> Result<(), DownloadError> + 'a;
+
+/// Whatever a map was released in, read one entry at a time so the extraction and progress
+/// reporting stay the same for every format.
+pub trait MapArchive {
+    fn entry_names(&mut self) -> Result<Vec<String>, DownloadError>;
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<(), DownloadError>;
+}
+
+pub fn open_archive(path: &Path) -> Result<Box<dyn MapArchive>, DownloadError> {
+    debug!("Opening archive {:?}", path);
+    match ArchiveKind::detect(path) {
+        Some(ArchiveKind::Zip) => {
+            let archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+            Ok(Box::new(ZipMapArchive { archive }))
+        }
+        Some(ArchiveKind::SevenZip) => Ok(Box::new(SevenZMapArchive {
+            path: path.to_path_buf(),
+        })),
+        Some(ArchiveKind::Rar) => open_rar(path),
+        // numbered when it's unpacked, once we know which paks are already there
+        Some(ArchiveKind::Pak) | Some(ArchiveKind::Bsp) => {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            Ok(Box::new(RawFileArchive {
+                path: path.to_path_buf(),
+                name: name.to_string(),
+            }))
+        }
+        None => Err(DownloadError::UnsupportedFormat(
+            path.to_string_lossy().to_string(),
+        )),
+    }
+}
+
+struct ZipMapArchive<R: Read + Seek> {
+    archive: ZipArchive<R>,
+}
+
+impl<R: Read + Seek> MapArchive for ZipMapArchive<R> {
+    fn entry_names(&mut self) -> Result<Vec<String>, DownloadError> {
+        Ok(self
+            .archive
+            .file_names()
+            .map(|name| name.to_owned())
+            .collect())
+    }
+
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<(), DownloadError> {
+        let count = self.archive.len();
+        for index in 0..count {
+            let mut file = self.archive.by_index(index)?;
+            let entry = ArchiveEntry {
+                name: file.name().to_owned(),
+                is_dir: file.is_dir(),
+                is_symlink: is_symlink_entry(file.unix_mode()),
+                index,
+                count,
+            };
+            visit(&entry, &mut file)?;
+        }
+        Ok(())
+    }
+}
+
+struct SevenZMapArchive {
+    path: PathBuf,
+}
+
+impl SevenZMapArchive {
+    fn open(&self) -> Result<SevenZReader<File>, DownloadError> {
+        SevenZReader::open(&self.path, Password::empty()).map_err(invalid_archive)
+    }
+
+    // just the headers, the reader keeps its copy to itself
+    fn read_archive(&self) -> Result<Archive, DownloadError> {
+        let mut file = File::open(&self.path)?;
+        let len = file.metadata()?.len();
+        Archive::read(&mut file, len, &[]).map_err(invalid_archive)
+    }
+}
+
+impl MapArchive for SevenZMapArchive {
+    fn entry_names(&mut self) -> Result<Vec<String>, DownloadError> {
+        Ok(self
+            .read_archive()?
+            .files
+            .iter()
+            .map(|file| file.name().to_owned())
+            .collect())
+    }
+
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<(), DownloadError> {
+        let count = self.read_archive()?.files.len();
+        let mut reader = self.open()?;
+        let mut index = 0;
+        let mut visit_error = None;
+        reader
+            .for_each_entries(|file, entry_reader| {
+                let entry = ArchiveEntry {
+                    name: file.name().to_owned(),
+                    is_dir: file.is_directory(),
+                    is_symlink: false,
+                    index,
+                    count,
+                };
+                index += 1;
+                match visit(&entry, entry_reader) {
+                    Ok(()) => Ok(true),
+                    Err(e) => {
+                        // stop reading, the error gets handed back below
+                        visit_error = Some(e);
+                        Ok(false)
+                    }
+                }
+            })
+            .map_err(invalid_archive)?;
+        match visit_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "rar")]
+fn open_rar(path: &Path) -> Result<Box<dyn MapArchive>, DownloadError> {
+    // unrar only extracts to disk, so unpack to a scratch dir and read back from there
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let scratch_dir = std::env::temp_dir().join(format!("rq_injector-{}", stem));
+    if scratch_dir.exists() {
+        remove_dir_all(&scratch_dir)?;
+    }
+    create_dir_all(&scratch_dir)?;
+    unrar::Archive::new(path.to_string_lossy().to_string())
+        .extract_to(scratch_dir.to_string_lossy().to_string())
+        .map_err(|e| invalid_archive(format!("{:?}", e)))?
+        .process()
+        .map_err(|e| invalid_archive(format!("{:?}", e)))?;
+    Ok(Box::new(DirMapArchive { root: scratch_dir }))
+}
+
+#[cfg(not(feature = "rar"))]
+fn open_rar(path: &Path) -> Result<Box<dyn MapArchive>, DownloadError> {
+    warn!("Built without rar support, can't open {:?}", path);
+    Err(DownloadError::UnsupportedFormat(
+        path.to_string_lossy().to_string(),
+    ))
+}
+
+/// An archive that has already been unpacked somewhere temporary. The dir goes away with it.
+#[cfg(feature = "rar")]
+struct DirMapArchive {
+    root: PathBuf,
+}
+
+#[cfg(feature = "rar")]
+impl DirMapArchive {
+    fn collect(&self, dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), DownloadError> {
+        for entry in read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.collect(&path, paths)?;
+            } else {
+                paths.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn relative_names(&self) -> Result<Vec<(String, PathBuf)>, DownloadError> {
+        let mut paths = vec![];
+        self.collect(&self.root, &mut paths)?;
+        Ok(paths
+            .into_iter()
+            .map(|path| {
+                let relative_path = path.strip_prefix(&self.root).unwrap_or(&path);
+                (relative_path.to_string_lossy().replace('\\', "/"), path)
+            })
+            .collect())
+    }
+}
+
+#[cfg(feature = "rar")]
+impl MapArchive for DirMapArchive {
+    fn entry_names(&mut self) -> Result<Vec<String>, DownloadError> {
+        Ok(self
+            .relative_names()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<(), DownloadError> {
+        let names = self.relative_names()?;
+        let count = names.len();
+        for (index, (name, path)) in names.into_iter().enumerate() {
+            let entry = ArchiveEntry {
+                name,
+                is_dir: false,
+                is_symlink: std::fs::symlink_metadata(&path)?.file_type().is_symlink(),
+                index,
+                count,
+            };
+            visit(&entry, &mut BufReader::new(File::open(&path)?))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rar")]
+impl Drop for DirMapArchive {
+    fn drop(&mut self) {
+        if let Err(e) = remove_dir_all(&self.root) {
+            warn!("Couldn't clean up {:?}: {}", self.root, e);
+        }
+    }
+}
+
+/// A lone pak or bsp released without any archive around it.
+struct RawFileArchive {
+    path: PathBuf,
+    name: String,
+}
+
+impl MapArchive for RawFileArchive {
+    fn entry_names(&mut self) -> Result<Vec<String>, DownloadError> {
+        Ok(vec![self.name.clone()])
+    }
+
+    fn for_each_entry(&mut self, visit: &mut EntryVisitor) -> Result<(), DownloadError> {
+        let entry = ArchiveEntry {
+            name: self.name.clone(),
+            is_dir: false,
+            is_symlink: false,
+            index: 0,
+            count: 1,
+        };
+        visit(&entry, &mut BufReader::new(File::open(&self.path)?))
+    }
+}
+
+fn invalid_archive<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> DownloadError {
+    DownloadError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Checks every zip in the download dir against the checksums in the database.
+pub fn verify_cached_zips(download_dir: &String, files: &[QuakeFile]) -> Vec<(String, ZipStatus)> {
+    info!("Verifying cached zips in {}", download_dir);
+    let entries = match read_dir(download_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("Couldn't read download dir {}: {}", download_dir, e);
+            return vec![];
+        }
+    };
+    let mut report = vec![];
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("zip") {
+            continue;
+        }
+        let map_id = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) => stem.to_owned(),
+            None => continue,
+        };
+        let status = match files.iter().find(|f| f.id() == &map_id) {
+            Some(file) => get_zip_status(&path, file.md5()),
+            None => ZipStatus::Unknown,
+        };
+        debug!("{}: {:?}", map_id, status);
+        report.push((map_id, status));
+    }
+    report.sort_by(|a, b| a.0.cmp(&b.0));
+    report
+}
+
+fn get_zip_status(path: &Path, md5: &String) -> ZipStatus {
+    if check_md5(path, md5).is_ok() {
+        return ZipStatus::Valid;
+    }
+    let read_everything = || -> Result<(), DownloadError> {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+        for i in 0..archive.len() {
+            // reading each entry through makes the zip crate check its crc
+            std::io::copy(&mut archive.by_index(i)?, &mut std::io::sink())?;
+        }
+        Ok(())
+    };
+    match read_everything() {
+        Ok(_) => ZipStatus::Stale,
+        Err(e) => ZipStatus::Corrupt(e.to_string()),
+    }
+}
+
+pub fn file_md5<P: AsRef<Path>>(path: P) -> Result<String, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut context = md5::Context::new();
+    let mut buffer = [0; 0x4000];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        context.consume(&buffer[..count]);
+    }
+    Ok(format!("{:x}", context.compute()))
+}
+
+pub fn file_crc<P: AsRef<Path>>(path: P) -> Result<u32, std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut digest = crc32::Digest::new(crc32::IEEE);
+    let mut buffer = [0; 0x4000];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        digest.write(&buffer[..count]);
+    }
+    Ok(digest.sum32())
+}
+
+fn check_md5<P: AsRef<Path>>(path: P, expected: &String) -> Result<(), DownloadError> {
+    if expected.is_empty() {
+        return Ok(());
+    }
+    let actual = file_md5(&path)?;
+    match actual.eq_ignore_ascii_case(expected) {
+        true => Ok(()),
+        false => Err(DownloadError::Checksum {
+            file_name: path.as_ref().to_string_lossy().to_string(),
+            expected: expected.to_owned(),
+            actual,
+        }),
+    }
+}
@@ -0,0 +1,81 @@
+use crate::configuration::MapPack;
+use crate::installer::file_crc;
+use getset::Getters;
+use log::*;
+use std::collections::HashSet;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, Getters)]
+#[getset(get = "pub")]
+pub struct IntegrityReport {
+    map_id: String,
+    modified: Vec<String>,
+    missing: Vec<String>,
+    // files in the map's own dir that weren't part of the install, usually saves
+    extra: Vec<String>,
+    // installed before file lists were recorded, so there's nothing to compare against
+    is_untracked: bool,
+}
+
+impl IntegrityReport {
+    /// Extra files don't count, the engine writes saves and configs next to the map.
+    pub fn needs_repair(&self) -> bool {
+        self.is_untracked || !self.modified.is_empty() || !self.missing.is_empty()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        !self.needs_repair() && self.extra.is_empty()
+    }
+}
+
+/// Compares the files on disk against the CRCs recorded when the map was installed.
+pub fn verify_installation(quake_dir: &String, map_pack: &MapPack) -> IntegrityReport {
+    info!("Verifying installation of {}", map_pack.id());
+    let mut report = IntegrityReport {
+        map_id: map_pack.id().to_owned(),
+        ..Default::default()
+    };
+    if map_pack.files().is_empty() {
+        report.is_untracked = true;
+        return report;
+    }
+    let quake_path = Path::new(quake_dir);
+    for file_info in map_pack.files() {
+        match file_crc(quake_path.join(file_info.name())) {
+            Ok(crc) if crc == *file_info.crc() => (),
+            Ok(_) => report.modified.push(file_info.name().to_owned()),
+            Err(e) => {
+                debug!("Can't read {}: {}", file_info.name(), e);
+                report.missing.push(file_info.name().to_owned());
+            }
+        }
+    }
+    let recorded: HashSet<&String> = map_pack.files().iter().map(|f| f.name()).collect();
+    let mut on_disk = vec![];
+    collect_files(&quake_path.join(map_pack.id()), &mut on_disk);
+    for path in on_disk {
+        let relative_path = path.strip_prefix(quake_path).unwrap_or(&path);
+        let name = relative_path.to_string_lossy().replace('\\', "/");
+        if !recorded.contains(&name) {
+            report.extra.push(name);
+        }
+    }
+    debug!("Integrity report: {:?}", report);
+    report
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
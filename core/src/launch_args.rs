@@ -0,0 +1,211 @@
+use crate::configuration::EngineProfile;
+use derive_builder::Builder;
+use getset::{Getters, Setters};
+use log::*;
+use std::fmt;
+
+// flags that only make sense once, the last one given wins
+enum SingleValueFlag {
+    Basedir,
+    Game,
+    Map,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LaunchArgsError {
+    UnclosedQuote { quote: char, line: String },
+}
+
+impl fmt::Display for LaunchArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchArgsError::UnclosedQuote { quote, line } => {
+                write!(f, "Missing closing {} in '{}'", quote, line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LaunchArgsError {}
+
+/// Everything that goes into starting a map. Options are layered: the database's
+/// `commandline`, then the engine's own, then the user's global options, then the ones saved
+/// for this map, each able to override the one before.
+#[derive(Builder, Clone, Debug, Getters, Setters)]
+#[getset(get = "pub", set = "pub")]
+pub struct LaunchArgs {
+    // with its quake dir already filled in, see `Configuration::get_engine`
+    engine: EngineProfile,
+    map_id: String,
+    // picked by the user, beats any +map in the options
+    #[builder(default)]
+    start_map: Option<String>,
+    #[builder(default)]
+    database_line: Option<String>,
+    #[builder(default)]
+    user_options: String,
+    #[builder(default)]
+    map_options: String,
+}
+
+impl LaunchArgs {
+    /// The arguments to hand the engine, not including the engine itself.
+    pub fn to_argv(&self) -> Result<Vec<String>, LaunchArgsError> {
+        let mut tokens = vec![];
+        if let Some(line) = &self.database_line {
+            tokens.append(&mut split_args(line)?);
+        }
+        tokens.append(&mut split_args(self.engine.launch_options())?);
+        tokens.append(&mut split_args(&self.user_options)?);
+        tokens.append(&mut split_args(&self.map_options)?);
+
+        let game_flag = self.engine.game_flag();
+        let mut basedir = self.engine.quake_dir().clone();
+        let mut game = self.map_id.clone();
+        let mut map = None;
+        let mut rest = vec![];
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            // the database always says -game, whatever this engine calls it. a -basedir the
+            // engine doesn't get from us is the user's business and goes through as is
+            let flag = match token.to_ascii_lowercase().as_str() {
+                "-basedir" if *self.engine.uses_basedir() => SingleValueFlag::Basedir,
+                "-game" => SingleValueFlag::Game,
+                "+map" => SingleValueFlag::Map,
+                _ if !game_flag.is_empty() && token.eq_ignore_ascii_case(game_flag) => {
+                    SingleValueFlag::Game
+                }
+                _ => {
+                    rest.push(token);
+                    continue;
+                }
+            };
+            let value = match tokens.peek() {
+                Some(next) if !next.starts_with('-') && !next.starts_with('+') => {
+                    tokens.next().unwrap()
+                }
+                _ => {
+                    warn!("Dropping {} without a value", token);
+                    continue;
+                }
+            };
+            debug!("{} set to {}", token, value);
+            match flag {
+                SingleValueFlag::Basedir => basedir = value,
+                SingleValueFlag::Game => game = value,
+                SingleValueFlag::Map => map = Some(value),
+            }
+        }
+        if let Some(start_map) = &self.start_map {
+            map = Some(start_map.clone());
+        }
+
+        let mut argv = vec![];
+        if *self.engine.uses_basedir() {
+            argv.push("-basedir".to_string());
+            argv.push(basedir);
+        }
+        // an engine without a game flag can't be pointed at the map's dir at all
+        if !game_flag.is_empty() {
+            argv.push(game_flag.to_owned());
+            argv.push(game);
+        }
+        argv.append(&mut rest);
+        // last so any +set commands before it are already applied when the map loads
+        if let Some(map) = map {
+            argv.push("+map".to_string());
+            argv.push(map);
+        }
+        Ok(argv)
+    }
+}
+
+/// Splits a command line the way a shell would: whitespace separates arguments, single quotes
+/// keep everything as is, double quotes keep spaces and a backslash escapes a quote, a space
+/// or another backslash. Any other backslash is left alone so Windows paths survive.
+pub fn split_args(line: &str) -> Result<Vec<String>, LaunchArgsError> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '\'' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(unclosed('\'', line)),
+                    }
+                }
+            }
+            '"' => {
+                in_arg = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.peek() {
+                            Some(&next) if next == '"' || next == '\\' => {
+                                current.push(next);
+                                chars.next();
+                            }
+                            _ => current.push('\\'),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(unclosed('"', line)),
+                    }
+                }
+            }
+            '\\' => {
+                in_arg = true;
+                match chars.peek() {
+                    Some(&next) if is_escapable(next) => {
+                        current.push(next);
+                        chars.next();
+                    }
+                    _ => current.push('\\'),
+                }
+            }
+            c => {
+                in_arg = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// The reverse of `split_args`, for showing an argv to the user.
+pub fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let needs_quotes = arg.is_empty() || arg.chars().any(is_escapable);
+            match needs_quotes {
+                true => format!("'{}'", arg.replace('\'', r#"'"'"'"#)),
+                false => arg.to_owned(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn is_escapable(c: char) -> bool {
+    c.is_whitespace() || c == '"' || c == '\'' || c == '\\'
+}
+
+fn unclosed(quote: char, line: &str) -> LaunchArgsError {
+    LaunchArgsError::UnclosedQuote {
+        quote,
+        line: line.to_owned(),
+    }
+}
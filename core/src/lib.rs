@@ -0,0 +1,23 @@
+//! Everything that doesn't need a window: the database, downloads, installs, launching the
+//! game and the files we keep in the config dir. Progress goes out through
+//! `download_progress::ProgressReporter`, so any front end can sit on top of this.
+
+pub mod configuration;
+pub mod database_refresh;
+pub mod dependencies;
+pub mod download_error;
+pub mod download_progress;
+pub mod download_queue;
+pub mod game_player;
+pub mod http;
+pub mod image_loader;
+pub mod install_detection;
+pub mod installer;
+pub mod integrity;
+pub mod launch_args;
+pub mod locales;
+pub mod quake_file;
+pub mod quake_paths;
+pub mod request_utils;
+pub mod safe_extract;
+pub mod whats_new;
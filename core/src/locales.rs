@@ -70,6 +70,8 @@ pub struct Locale {
     reload_database_menu_option: String,
     check_installed_menu_option: String,
     clear_cache_menu_option: String,
+    verify_cache_menu_option: String,
+    verify_installs_menu_option: String,
     offline_menu_option: String,
     quit_menu_option: String,
     configuration_menu_name: String,
@@ -94,6 +96,7 @@ pub struct Locale {
     config_dialog_expansion_pack_text: String,
     config_dialog_expansion_hip_text: String,
     config_dialog_expansion_rogue_text: String,
+    config_dialog_max_downloads_text: String,
     config_dialog_select_button_text: String,
     config_dialog_personal_title: String,
     config_dialog_language_selector: String,
@@ -105,6 +108,89 @@ pub struct Locale {
     output_dialog_title: String,
     database_corrupt_error: String,
     file_chooser_title: String,
+    verify_cache_all_valid_text: String,
+    verify_cache_corrupt_text: String,
+    verify_cache_stale_text: String,
+    queue_state_queued: String,
+    queue_state_running: String,
+    queue_state_failed: String,
+    queue_state_done: String,
+    queue_state_cancelled: String,
+    queue_retry_button: String,
+    queue_remove_button: String,
+    uninstall_kept_files_text: String,
+    verify_button_text: String,
+    integrity_all_intact_text: String,
+    integrity_modified_text: String,
+    integrity_missing_text: String,
+    integrity_extra_text: String,
+    integrity_untracked_text: String,
+    integrity_repair_question: String,
+    integrity_repaired_text: String,
+    install_file_menu_option: String,
+    install_url_menu_option: String,
+    custom_dialog_title: String,
+    custom_dialog_source_text: String,
+    custom_dialog_id_text: String,
+    custom_dialog_title_text: String,
+    custom_dialog_file_chooser_text: String,
+    custom_invalid_id_text: String,
+    custom_id_taken_text: String,
+    custom_installed_text: String,
+    config_dialog_database_max_age_text: String,
+    database_up_to_date_text: String,
+    database_added_text: String,
+    database_updated_text: String,
+    database_removed_text: String,
+    whats_new_button: String,
+    mark_seen_button: String,
+    whats_new_added_text: String,
+    whats_new_updated_text: String,
+    whats_new_rerated_text: String,
+    database_unavailable_text: String,
+    database_offline_text: String,
+    database_fallback_text: String,
+    database_retry_button: String,
+    config_dialog_network_tab: String,
+    config_dialog_network_title: String,
+    config_dialog_proxy_text: String,
+    config_dialog_proxy_user_text: String,
+    config_dialog_proxy_password_text: String,
+    config_dialog_connect_timeout_text: String,
+    config_dialog_read_timeout_text: String,
+    config_dialog_retries_text: String,
+    launch_dialog_title: String,
+    launch_dialog_play_button: String,
+    launch_dialog_command_text: String,
+    launch_dialog_map_options_text: String,
+    launch_options_invalid_text: String,
+    engine_choice_tooltip: String,
+    config_dialog_engines_tab: String,
+    config_dialog_engines_title: String,
+    config_dialog_engine_name_text: String,
+    config_dialog_engine_dir_text: String,
+    config_dialog_engine_options_text: String,
+    config_dialog_engine_game_flag_text: String,
+    config_dialog_engine_basedir_text: String,
+    config_dialog_engine_save_button: String,
+    config_dialog_engine_remove_button: String,
+    kill_game_button: String,
+    game_running_text: String,
+    game_exited_text: String,
+    game_killed_text: String,
+    game_start_failed_text: String,
+    config_dialog_detected_text: String,
+    config_dialog_detect_button: String,
+    config_dialog_use_detected_button: String,
+    config_dialog_detected_none_text: String,
+    config_dialog_detected_no_engine_text: String,
+    config_dialog_expansion_detected_tooltip: String,
+    config_dialog_invalid_paths_text: String,
+    path_not_a_directory_text: String,
+    path_no_game_data_text: String,
+    path_not_a_file_text: String,
+    path_not_executable_text: String,
+    mission_pack_missing_text: String,
 }
 
 pub fn init_locale(locale_dir: PathBuf, locale_choice: String) -> Locale {
@@ -136,7 +222,8 @@ fn read_locale_file<S: AsRef<str> + Display>(dir: PathBuf, name: S) -> Result<Lo
     let mut file_path = dir.clone();
     let file_name = format!("{}.xml", name);
     file_path.push(file_name);
-    let file = File::open(&file_path).expect(&format!("No locale file keyed by name: {}", name));
+    let file =
+        File::open(&file_path).unwrap_or_else(|_| panic!("No locale file keyed by name: {}", name));
     let reader = BufReader::new(file);
     from_reader(reader)
 }
@@ -0,0 +1,193 @@
+use crate::configuration::{MapPack, Mirror};
+use crate::database_refresh::refresh_database;
+use crate::download_error::DownloadError;
+use getset::Getters;
+use log::*;
+use quick_xml::de::{from_reader, DeError};
+use serde::Deserialize;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+pub const DATABASE_FILE_NAME: &str = "database.xml";
+pub const BACKUP_FILE_NAME: &str = "database.backup.xml";
+// optional copy shipped next to the binary, for a first run without a connection. Looked up
+// from the executable's dir so it doesn't matter where the injector is started from
+const BUNDLED_DATABASE_PATH: &str = "resources/database.xml";
+
+#[derive(Debug)]
+pub enum DatabaseError {
+    Offline,
+    Unavailable(DownloadError),
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseError::Offline => write!(f, "Offline mode is on and there's no local database"),
+            DatabaseError::Unavailable(e) => write!(f, "Couldn't get the database: {}", e),
+        }
+    }
+}
+
+/// Loads the database, fetching it when there isn't a usable local copy. When that fails the
+/// last known-good or bundled copy is used instead and the error comes back alongside it.
+pub fn initialize_data(
+    config_dir: PathBuf,
+    is_offline: bool,
+    mirrors: &[Mirror],
+) -> (Files, Option<DatabaseError>) {
+    trace!("Initializing data");
+    let file_path = config_dir.join(DATABASE_FILE_NAME);
+    if let Some(files) = read_database_file(&file_path) {
+        debug!("Local database found. Using");
+        keep_known_good(&config_dir);
+        return (Files { files }, None);
+    }
+    if file_path.exists() {
+        // out of the way of the next fetch, but still around to look at
+        let corrupt_path = file_path.with_extension("xml.corrupt");
+        warn!("Moving unreadable database to {:?}", corrupt_path);
+        if let Err(e) = fs::rename(&file_path, &corrupt_path) {
+            error!("Couldn't move the unreadable database: {}", e);
+        }
+    }
+    let error = match is_offline {
+        true => DatabaseError::Offline,
+        false => match refresh_database(&config_dir, mirrors) {
+            Ok(_) => match read_database_file(&file_path) {
+                Some(files) => return (Files { files }, None),
+                None => DatabaseError::Unavailable(
+                    Error::new(ErrorKind::NotFound, "no database after refresh").into(),
+                ),
+            },
+            Err(e) => DatabaseError::Unavailable(e),
+        },
+    };
+    error!("{}", error);
+    let files = read_fallback_database(&config_dir).unwrap_or_default();
+    (Files { files }, Some(error))
+}
+
+fn read_fallback_database(config_dir: &Path) -> Option<Vec<QuakeFile>> {
+    let backup_path = config_dir.join(BACKUP_FILE_NAME);
+    if let Some(files) = read_database_file(&backup_path) {
+        info!("Using the last known-good database");
+        return Some(files);
+    }
+    let files = read_database_file(&bundled_database_path()?)?;
+    info!("Using the bundled database");
+    Some(files)
+}
+
+fn bundled_database_path() -> Option<PathBuf> {
+    let exe_path = std::env::current_exe().ok()?;
+    Some(exe_path.parent()?.join(BUNDLED_DATABASE_PATH))
+}
+
+/// Copies a database that just parsed to the backup, unless the backup is already as new.
+pub fn keep_known_good(config_dir: &Path) {
+    let file_path = config_dir.join(DATABASE_FILE_NAME);
+    let backup_path = config_dir.join(BACKUP_FILE_NAME);
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let is_current = match (modified(&file_path), modified(&backup_path)) {
+        (Some(file_time), Some(backup_time)) => backup_time >= file_time,
+        _ => false,
+    };
+    if is_current {
+        return;
+    }
+    debug!("Backing up the database");
+    if let Err(e) = fs::copy(&file_path, &backup_path) {
+        warn!("Couldn't back up the database: {}", e);
+    }
+}
+
+/// Reads a database file, a missing or broken one is just `None`.
+pub fn read_database_file(file_path: &Path) -> Option<Vec<QuakeFile>> {
+    let file = File::open(file_path).ok()?;
+    let file_result: Result<Files, DeError> = from_reader(BufReader::new(file));
+    match file_result {
+        Ok(files) => Some(files.files),
+        Err(e) => {
+            warn!("Couldn't parse {:?}: {}", file_path, e);
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters)]
+#[getset(get = "pub")]
+pub struct Files {
+    #[serde(rename = "file", default)]
+    files: Vec<QuakeFile>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters)]
+#[serde(rename(deserialize = "file"))]
+#[getset(get = "pub")]
+pub struct QuakeFile {
+    id: String,
+    #[serde(rename = "type")]
+    kind: u8,
+    rating: String,
+    author: String,
+    title: String,
+    #[serde(rename = "md5sum")]
+    md5: String,
+    size: String,
+    date: String,
+    description: String,
+    #[serde(rename = "techinfo")]
+    tech_info: TechInfo,
+    #[serde(skip)]
+    installed_locally: bool,
+}
+
+impl QuakeFile {
+    /// Stands in for a map the user installed themselves, so it can sit in the list with the
+    /// database ones.
+    pub fn custom(map_pack: &MapPack) -> Self {
+        let title = match map_pack.title().is_empty() {
+            true => map_pack.id().to_owned(),
+            false => map_pack.title().to_owned(),
+        };
+        Self {
+            id: map_pack.id().to_owned(),
+            kind: 1,
+            rating: String::from("0"),
+            title,
+            date: map_pack.date().to_owned(),
+            description: map_pack.source().to_owned(),
+            installed_locally: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters)]
+#[getset(get = "pub")]
+pub struct TechInfo {
+    #[serde(default, rename = "zipbasedir")]
+    zip_base_dir: Option<String>,
+    #[serde(default, rename = "commandline")]
+    command_line: Option<String>,
+    #[serde(default, rename = "startmap")]
+    start_map: Vec<String>,
+    #[serde(default)]
+    requirements: Option<Requirements>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters)]
+#[getset(get = "pub")]
+pub struct Requirements {
+    #[serde(rename = "file")]
+    req_file: Vec<ReqFile>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Getters)]
+#[getset(get = "pub")]
+pub struct ReqFile {
+    id: String,
+}
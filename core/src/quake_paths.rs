@@ -0,0 +1,156 @@
+use crate::install_detection::{find_entry, is_quake_dir};
+use crate::launch_args::split_args;
+use crate::quake_file::QuakeFile;
+use log::*;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MissionPack {
+    Hipnotic,
+    Rogue,
+}
+
+impl MissionPack {
+    pub const ALL: [MissionPack; 2] = [MissionPack::Hipnotic, MissionPack::Rogue];
+
+    /// The directory it lives in under the Quake dir, also its `-game` and its own flag.
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            MissionPack::Hipnotic => "hipnotic",
+            MissionPack::Rogue => "rogue",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        MissionPack::ALL
+            .iter()
+            .copied()
+            .find(|pack| id.eq_ignore_ascii_case(pack.dir_name()))
+    }
+
+    /// Looks for the pack's `pak0.pak`, an empty directory doesn't count.
+    pub fn is_installed<P: AsRef<Path>>(&self, quake_dir: P) -> bool {
+        let quake_dir = quake_dir.as_ref();
+        // an empty path would look in the current dir
+        if quake_dir.as_os_str().is_empty() {
+            return false;
+        }
+        match find_entry(quake_dir, self.dir_name()) {
+            Some(dir) => find_entry(&dir, "pak0.pak").is_some_and(|pak| pak.is_file()),
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for MissionPack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MissionPack::Hipnotic => "Scourge of Armagon",
+            MissionPack::Rogue => "Dissolution of Eternity",
+        };
+        write!(f, "{} ({})", name, self.dir_name())
+    }
+}
+
+/// The mission packs a map is built on, going by its command line and requirements.
+pub fn required_mission_packs(file: &QuakeFile) -> Vec<MissionPack> {
+    let mut packs = vec![];
+    let mut add = |pack| {
+        if !packs.contains(&pack) {
+            packs.push(pack);
+        }
+    };
+    if let Some(command_line) = file.tech_info().command_line() {
+        let tokens = split_args(command_line).unwrap_or_else(|e| {
+            warn!("Odd command line for {}: {}", file.id(), e);
+            command_line.split_whitespace().map(String::from).collect()
+        });
+        let mut tokens = tokens.iter();
+        while let Some(token) = tokens.next() {
+            // both -hipnotic and -game hipnotic are seen in the wild
+            let token = token.to_ascii_lowercase();
+            let name = match token.as_str() {
+                "-game" => tokens.next().map(|value| value.as_str()),
+                flag => flag.strip_prefix('-'),
+            };
+            if let Some(pack) = name.and_then(MissionPack::from_id) {
+                add(pack);
+            }
+        }
+    }
+    if let Some(requirements) = file.tech_info().requirements() {
+        for req in requirements.req_file() {
+            if let Some(pack) = MissionPack::from_id(req.id()) {
+                add(pack);
+            }
+        }
+    }
+    packs
+}
+
+/// What's wrong with a configured path, empty ones are left to whoever needs them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathError {
+    NotADirectory(String),
+    NoGameData(String),
+    NotAFile(String),
+    NotExecutable(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::NotADirectory(path) => write!(f, "'{}' is not a directory", path),
+            PathError::NoGameData(path) => write!(f, "'{}' has no id1/pak0.pak", path),
+            PathError::NotAFile(path) => write!(f, "'{}' is not a file", path),
+            PathError::NotExecutable(path) => write!(f, "'{}' is not executable", path),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+pub fn validate_quake_dir(quake_dir: &str) -> Result<(), PathError> {
+    validate_dir(quake_dir)?;
+    match quake_dir.is_empty() || is_quake_dir(quake_dir) {
+        true => Ok(()),
+        false => Err(PathError::NoGameData(quake_dir.to_owned())),
+    }
+}
+
+pub fn validate_quake_exe(quake_exe: &str) -> Result<(), PathError> {
+    if quake_exe.is_empty() {
+        return Ok(());
+    }
+    let path = Path::new(quake_exe);
+    if !path.is_file() {
+        return Err(PathError::NotAFile(quake_exe.to_owned()));
+    }
+    match is_executable(path) {
+        true => Ok(()),
+        false => Err(PathError::NotExecutable(quake_exe.to_owned())),
+    }
+}
+
+pub fn validate_dir(dir: &str) -> Result<(), PathError> {
+    match dir.is_empty() || Path::new(dir).is_dir() {
+        true => Ok(()),
+        false => Err(PathError::NotADirectory(dir.to_owned())),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match path.metadata() {
+        Ok(metadata) => metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+// anything can be run on windows, it's the extension that decides how
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
@@ -0,0 +1,332 @@
+use crate::configuration::{default_mirrors, Mirror};
+use crate::download_error::DownloadError;
+use crate::download_progress::{DownloadProgress, ProgressReporter};
+use crate::http::{block_on, client, read_timeout, retries};
+use bytes::Bytes;
+use log::*;
+use reqwest::header::{HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE};
+use reqwest::{Response, StatusCode};
+use std::fmt::Debug;
+use std::fs::{metadata, remove_file, rename, write, OpenOptions};
+use std::future::Future;
+use std::io::prelude::*;
+use std::io::{BufWriter, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub enum DatabaseFetch {
+    NotModified,
+    Updated { etag: String, last_modified: String },
+}
+
+/// Only downloads the database when the server says it changed since `etag`/`last_modified`.
+pub fn get_database_if_changed<P: AsRef<Path> + Debug>(
+    file_path: P,
+    etag: &str,
+    last_modified: &str,
+    mirrors: &[Mirror],
+) -> Result<DatabaseFetch, DownloadError> {
+    debug!("Getting database from remote");
+    debug!("Database file path: {:?}", file_path);
+    let file_path = file_path.as_ref();
+    let urls = mirror_urls(mirrors, |mirror| mirror.database_url().to_owned());
+    block_on(try_mirrors(urls, move |url| {
+        get_database_from_url(url, file_path, etag, last_modified)
+    }))
+}
+
+async fn get_database_from_url(
+    url: String,
+    file_path: &Path,
+    etag: &str,
+    last_modified: &str,
+) -> Result<DatabaseFetch, DownloadError> {
+    let mut request = client().get(&url);
+    if !etag.is_empty() {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if !last_modified.is_empty() {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    let mut response = with_read_timeout(request.send()).await?;
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        info!("Database hasn't changed on the server");
+        return Ok(DatabaseFetch::NotModified);
+    }
+    if !status.is_success() {
+        return Err(DownloadError::HttpStatus {
+            url,
+            status: status.as_u16(),
+        });
+    }
+    let get_header = |name: HeaderName| match response.headers().get(name) {
+        Some(value) => value.to_str().unwrap_or_default().to_owned(),
+        None => String::new(),
+    };
+    let etag = get_header(ETAG);
+    let last_modified = get_header(LAST_MODIFIED);
+    let mut bytes = vec![];
+    while let Some(chunk) = next_chunk(&mut response).await? {
+        bytes.extend_from_slice(&chunk);
+    }
+    // written next to the old one first so a dropped connection can't leave half a database
+    let part_path = get_part_path(file_path);
+    write(&part_path, &bytes)?;
+    rename(&part_path, file_path)?;
+    Ok(DatabaseFetch::Updated {
+        etag,
+        last_modified,
+    })
+}
+
+pub fn get_map_from_remote(
+    map_id: &String,
+    download_dir: &String,
+    mirrors: &[Mirror],
+    reporter: &dyn ProgressReporter,
+    cancel_flag: &AtomicBool,
+) -> Result<(), DownloadError> {
+    trace!("Getting map from remote");
+    let path = PathBuf::from(format!("{}/{}.zip", download_dir, map_id));
+    let path = path.as_path();
+    let urls = mirror_urls(mirrors, |mirror| mirror.get_map_url(map_id));
+    block_on(try_mirrors(urls, move |url| {
+        debug!("Getting map from url: {}", url);
+        get_remote_file_and_write_to_path(url, path, Some(reporter), Some(cancel_flag))
+    }))
+}
+
+pub fn get_file_from_url<P: AsRef<Path> + Debug>(
+    url: &String,
+    path: P,
+    reporter: &dyn ProgressReporter,
+    cancel_flag: &AtomicBool,
+) -> Result<(), DownloadError> {
+    debug!("Getting file from url: {}", url);
+    block_on(get_remote_file_and_write_to_path(
+        url.to_owned(),
+        path.as_ref(),
+        Some(reporter),
+        Some(cancel_flag),
+    ))
+}
+
+pub fn get_image_from_remote<P: AsRef<Path> + Debug>(
+    map_id: &str,
+    path: P,
+    mirrors: &[Mirror],
+) -> Result<(), DownloadError> {
+    block_on(fetch_image(map_id, path.as_ref(), mirrors))
+}
+
+/// The async side of `get_image_from_remote`, for fetching screenshots on the shared runtime
+/// without a thread each.
+pub async fn fetch_image(
+    map_id: &str,
+    path: &Path,
+    mirrors: &[Mirror],
+) -> Result<(), DownloadError> {
+    trace!("Getting image file from remote");
+    let urls = mirror_urls(mirrors, |mirror| mirror.get_screenshot_url(map_id));
+    try_mirrors(urls, move |url| {
+        debug!("Getting image from url: {}", url);
+        get_remote_file_and_write_to_path(url, path, None, None)
+    })
+    .await
+}
+
+// pairs each mirror's name with the url it has for a request. An empty list means the built
+// in ones
+fn mirror_urls<F>(mirrors: &[Mirror], get_url: F) -> Vec<(String, String)>
+where
+    F: Fn(&Mirror) -> String,
+{
+    let defaults = default_mirrors();
+    let mirrors = match mirrors.is_empty() {
+        true => &defaults[..],
+        false => mirrors,
+    };
+    mirrors
+        .iter()
+        .map(|mirror| (mirror.name().to_owned(), get_url(mirror)))
+        .collect()
+}
+
+/// Runs `request` against each mirror's url in turn until one of them works. Hiccups that
+/// might clear up get a few more goes on the same mirror first.
+async fn try_mirrors<T, F, Fut>(
+    urls: Vec<(String, String)>,
+    mut request: F,
+) -> Result<T, DownloadError>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<T, DownloadError>>,
+{
+    let retries = retries();
+    let mut last_error = None;
+    for (name, url) in urls {
+        for attempt in 0..=retries {
+            match request(url.clone()).await {
+                Ok(result) => return Ok(result),
+                Err(DownloadError::Cancelled) => return Err(DownloadError::Cancelled),
+                Err(e) => {
+                    warn!("Mirror {} failed (attempt {}): {}", name, attempt + 1, e);
+                    let is_transient = e.is_transient();
+                    last_error = Some(e);
+                    if !is_transient {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    Err(last_error.expect("No mirrors to try"))
+}
+
+async fn get_remote_file_and_write_to_path(
+    url: String,
+    path: &Path,
+    reporter_opt: Option<&dyn ProgressReporter>,
+    cancel_opt: Option<&AtomicBool>,
+) -> Result<(), DownloadError> {
+    let result = write_remote_file(url, path, reporter_opt, cancel_opt).await;
+    if let Err(e) = &result {
+        // the .part file is left behind on purpose so the next attempt can pick up from it
+        error!("Download to {:?} failed: {}", path, e);
+    }
+    result
+}
+
+async fn write_remote_file(
+    url: String,
+    path: &Path,
+    reporter_opt: Option<&dyn ProgressReporter>,
+    cancel_opt: Option<&AtomicBool>,
+) -> Result<(), DownloadError> {
+    let file_name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => {
+            let msg = format!("{:?} has no file name to download to", path);
+            return Err(Error::new(ErrorKind::InvalidInput, msg).into());
+        }
+    };
+    let part_path = get_part_path(path);
+    let (mut response, mut resumed_bytes) = loop {
+        let resumed_bytes = match metadata(&part_path) {
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        };
+        let mut request = client().get(&url);
+        if resumed_bytes > 0 {
+            debug!("Found {} bytes in {:?}, resuming", resumed_bytes, part_path);
+            request = request.header(RANGE, format!("bytes={}-", resumed_bytes));
+        }
+        let response = with_read_timeout(request.send()).await?;
+        if response.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+            break (response, resumed_bytes);
+        }
+        warn!("Server won't resume {:?}, starting over", part_path);
+        remove_file(&part_path)?;
+    };
+    let status = response.status();
+    if !status.is_success() {
+        return Err(DownloadError::HttpStatus {
+            url,
+            status: status.as_u16(),
+        });
+    }
+    let is_resuming = status == StatusCode::PARTIAL_CONTENT;
+    if resumed_bytes > 0 && !is_resuming {
+        debug!("Server ignored the range request, starting over");
+        resumed_bytes = 0;
+    }
+    let content_length = match response.content_length() {
+        Some(len) => resumed_bytes + len,
+        None => return Err(DownloadError::MissingLength(url)),
+    };
+    let part_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(is_resuming)
+        .truncate(!is_resuming)
+        .open(&part_path)?;
+    let mut file = BufWriter::new(part_file);
+
+    let mut total = resumed_bytes;
+    let mut progress_counter = 0.0;
+    while total < content_length {
+        if let Some(cancel_flag) = cancel_opt {
+            if cancel_flag.load(Ordering::Relaxed) {
+                info!("Download of {} cancelled at {} bytes", file_name, total);
+                file.flush()?;
+                return Err(DownloadError::Cancelled);
+            }
+        }
+        let chunk = match next_chunk(&mut response).await? {
+            Some(chunk) => chunk,
+            None => {
+                let msg = format!(
+                    "connection closed after {} of {} bytes",
+                    total, content_length
+                );
+                return Err(Error::new(ErrorKind::UnexpectedEof, msg).into());
+            }
+        };
+        let percent = match total != 0 {
+            true => total as f64 / content_length as f64,
+            false => 0.0,
+        };
+        let h_percent = percent * 100.0;
+        if h_percent.floor() > progress_counter {
+            progress_counter = h_percent.floor();
+            debug!("progress: {} %", h_percent);
+            let progress = DownloadProgress::not_done_dl(percent, &file_name);
+            send_progress(reporter_opt, progress.with_resumed_bytes(resumed_bytes));
+        }
+        file.write_all(&chunk)?;
+        total += chunk.len() as u64;
+    }
+    file.flush()?;
+    drop(file);
+    // only a finished download ever gets the real name
+    rename(&part_path, path)?;
+    let progress = DownloadProgress::not_done_dl(100.0, &file_name);
+    send_progress(reporter_opt, progress.with_resumed_bytes(resumed_bytes));
+    Ok(())
+}
+
+// a server that goes quiet, before the headers or mid download, counts as a failure once the
+// timeout passes
+async fn with_read_timeout<T, F>(request: F) -> Result<T, DownloadError>
+where
+    F: Future<Output = Result<T, reqwest::Error>>,
+{
+    let result = match read_timeout() {
+        Some(timeout) => match tokio::time::timeout(timeout, request).await {
+            Ok(result) => result,
+            Err(_) => {
+                let msg = format!("nothing received for {} seconds", timeout.as_secs());
+                return Err(Error::new(ErrorKind::TimedOut, msg).into());
+            }
+        },
+        None => request.await,
+    };
+    Ok(result?)
+}
+
+async fn next_chunk(response: &mut Response) -> Result<Option<Bytes>, DownloadError> {
+    with_read_timeout(response.chunk()).await
+}
+
+fn get_part_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut part_path = path.as_ref().as_os_str().to_owned();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+fn send_progress(reporter_opt: Option<&dyn ProgressReporter>, progress: DownloadProgress) {
+    if let Some(reporter) = reporter_opt {
+        reporter.report(progress);
+    }
+}
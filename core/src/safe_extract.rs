@@ -0,0 +1,273 @@
+use log::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{read_dir, symlink_metadata};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    Absolute,
+    ParentDir,
+    Empty,
+    Symlink(PathBuf),
+    Overwrite(PathBuf),
+    Duplicate(PathBuf),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Absolute => write!(f, "absolute paths aren't allowed"),
+            PathError::ParentDir => write!(f, "'..' isn't allowed"),
+            PathError::Empty => write!(f, "empty path"),
+            PathError::Symlink(path) => write!(f, "{:?} is a symlink", path),
+            PathError::Overwrite(path) => {
+                write!(f, "{:?} exists and doesn't belong to this map", path)
+            }
+            PathError::Duplicate(path) => {
+                write!(f, "{:?} was already extracted under another case", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Turns an archive entry name into plain path components. Windows-made archives use
+/// backslashes, so both separators count.
+pub fn sanitize_entry_name(name: &str) -> Result<Vec<String>, PathError> {
+    let normalized = name.replace('\\', "/");
+    let is_drive = normalized.chars().nth(1) == Some(':');
+    if normalized.starts_with('/') || is_drive {
+        return Err(PathError::Absolute);
+    }
+    let mut parts = vec![];
+    for part in normalized.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." => return Err(PathError::ParentDir),
+            // drive prefixes and alternate data streams on windows
+            part if part.contains(':') || part.contains('\0') => return Err(PathError::Absolute),
+            part => parts.push(part.to_owned()),
+        }
+    }
+    match parts.is_empty() {
+        true => Err(PathError::Empty),
+        false => Ok(parts),
+    }
+}
+
+/// Decides where each entry of one archive lands under `root`. Names are matched to whatever
+/// is already on disk ignoring case, so `MAPS/` and `maps/` end up in the same dir, and nothing
+/// is written through a symlink.
+pub struct SafeExtractor {
+    root: PathBuf,
+    // files that already exist may only be replaced when they are ours
+    can_overwrite: bool,
+    owned_files: HashSet<PathBuf>,
+    written: HashSet<String>,
+}
+
+impl SafeExtractor {
+    pub fn new(root: PathBuf, can_overwrite: bool, owned_files: HashSet<PathBuf>) -> Self {
+        Self {
+            root,
+            can_overwrite,
+            owned_files,
+            written: HashSet::new(),
+        }
+    }
+
+    pub fn resolve(&mut self, parts: &[String], is_dir: bool) -> Result<PathBuf, PathError> {
+        if parts.is_empty() {
+            return Err(PathError::Empty);
+        }
+        let mut path = self.root.clone();
+        for part in parts {
+            path = match_case(&path, part);
+            if is_symlink(&path) {
+                return Err(PathError::Symlink(path));
+            }
+        }
+        if is_dir {
+            return Ok(path);
+        }
+        let relative_path = path.strip_prefix(&self.root).unwrap_or(&path);
+        let key = relative_path.to_string_lossy().to_lowercase();
+        if !self.written.insert(key) {
+            return Err(PathError::Duplicate(path));
+        }
+        let is_owned = self.can_overwrite || self.owned_files.contains(&path);
+        if path.exists() && !is_owned {
+            return Err(PathError::Overwrite(path));
+        }
+        Ok(path)
+    }
+}
+
+fn match_case(dir: &Path, name: &str) -> PathBuf {
+    let existing = match read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .find(|entry_name| entry_name.eq_ignore_ascii_case(name)),
+        Err(_) => None,
+    };
+    match existing {
+        Some(existing) if existing != name => {
+            trace!("Using existing {} for {}", existing, name);
+            dir.join(existing)
+        }
+        _ => dir.join(name),
+    }
+}
+
+fn is_symlink(path: &Path) -> bool {
+    match symlink_metadata(path) {
+        Ok(metadata) => metadata.file_type().is_symlink(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, File};
+    use std::io::{Cursor, Write};
+    use zip::write::{FileOptions, ZipWriter};
+    use zip::ZipArchive;
+
+    // a fresh directory for each test, removed again when it's dropped
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("rq_injector_{}_{}", name, std::process::id()));
+            let _ = remove_dir_all(&path);
+            create_dir_all(&path).unwrap();
+            TestDir(path)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = remove_dir_all(&self.0);
+        }
+    }
+
+    fn parts(name: &str) -> Vec<String> {
+        sanitize_entry_name(name).unwrap()
+    }
+
+    #[test]
+    fn both_separators_split_entry_names() {
+        assert_eq!(
+            sanitize_entry_name("maps\\start.bsp"),
+            Ok(vec![String::from("maps"), String::from("start.bsp")])
+        );
+        assert_eq!(
+            sanitize_entry_name("./maps//start.bsp"),
+            Ok(parts("maps/start.bsp"))
+        );
+        assert_eq!(sanitize_entry_name("./"), Err(PathError::Empty));
+    }
+
+    #[test]
+    fn parent_dirs_are_rejected() {
+        assert_eq!(sanitize_entry_name("../x"), Err(PathError::ParentDir));
+        assert_eq!(
+            sanitize_entry_name("maps/../../x"),
+            Err(PathError::ParentDir)
+        );
+        assert_eq!(
+            sanitize_entry_name("maps\\..\\..\\x"),
+            Err(PathError::ParentDir)
+        );
+    }
+
+    #[test]
+    fn absolute_paths_are_rejected() {
+        assert_eq!(sanitize_entry_name("/etc/passwd"), Err(PathError::Absolute));
+        assert_eq!(
+            sanitize_entry_name("\\etc\\passwd"),
+            Err(PathError::Absolute)
+        );
+        assert_eq!(sanitize_entry_name("C:\\x"), Err(PathError::Absolute));
+        assert_eq!(
+            sanitize_entry_name("maps/start.bsp:stream"),
+            Err(PathError::Absolute)
+        );
+    }
+
+    #[test]
+    fn zip_slip_entries_stay_under_the_root() {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        for name in &[
+            "maps/evil.bsp",
+            "../../escaped.txt",
+            "maps/../../../escaped_too.txt",
+            "/tmp/rq_injector_absolute.txt",
+            "..\\..\\escaped_on_windows.txt",
+        ] {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(b"gotcha").unwrap();
+        }
+        let mut archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+        let dir = TestDir::new("zip_slip");
+        let mut extractor = SafeExtractor::new(dir.0.clone(), false, HashSet::new());
+        let mut resolved = vec![];
+        for i in 0..archive.len() {
+            let file = archive.by_index(i).unwrap();
+            if let Ok(parts) = sanitize_entry_name(file.name()) {
+                resolved.push(extractor.resolve(&parts, file.is_dir()).unwrap());
+            }
+        }
+        assert_eq!(resolved, vec![dir.0.join("maps").join("evil.bsp")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_not_followed() {
+        let dir = TestDir::new("symlink");
+        let outside = TestDir::new("symlink_target");
+        std::os::unix::fs::symlink(&outside.0, dir.0.join("maps")).unwrap();
+        let mut extractor = SafeExtractor::new(dir.0.clone(), true, HashSet::new());
+        assert_eq!(
+            extractor.resolve(&parts("maps/start.bsp"), false),
+            Err(PathError::Symlink(dir.0.join("maps")))
+        );
+    }
+
+    #[test]
+    fn only_owned_files_are_overwritten() {
+        let dir = TestDir::new("overwrite");
+        File::create(dir.0.join("pak0.pak")).unwrap();
+        File::create(dir.0.join("autoexec.cfg")).unwrap();
+        let owned = vec![dir.0.join("autoexec.cfg")].into_iter().collect();
+        let mut extractor = SafeExtractor::new(dir.0.clone(), false, owned);
+        assert_eq!(
+            extractor.resolve(&parts("pak0.pak"), false),
+            Err(PathError::Overwrite(dir.0.join("pak0.pak")))
+        );
+        assert_eq!(
+            extractor.resolve(&parts("autoexec.cfg"), false),
+            Ok(dir.0.join("autoexec.cfg"))
+        );
+    }
+
+    #[test]
+    fn names_follow_the_case_on_disk() {
+        let dir = TestDir::new("case");
+        create_dir_all(dir.0.join("maps")).unwrap();
+        let mut extractor = SafeExtractor::new(dir.0.clone(), false, HashSet::new());
+        assert_eq!(
+            extractor.resolve(&parts("MAPS/start.bsp"), false),
+            Ok(dir.0.join("maps").join("start.bsp"))
+        );
+        assert_eq!(
+            extractor.resolve(&parts("maps/START.BSP"), false),
+            Err(PathError::Duplicate(dir.0.join("maps").join("START.BSP")))
+        );
+    }
+}
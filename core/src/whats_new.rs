@@ -0,0 +1,35 @@
+use crate::configuration::SeenMaps;
+use crate::quake_file::QuakeFile;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MapChange {
+    Added,
+    Updated,
+    Rerated,
+}
+
+/// Everything in `files` that's new or different since the seen snapshot was taken.
+pub fn find_changes(files: &[QuakeFile], seen_maps: &SeenMaps) -> HashMap<String, MapChange> {
+    let seen_by_id: HashMap<&String, _> = seen_maps
+        .maps()
+        .iter()
+        .map(|seen| (seen.id(), seen))
+        .collect();
+    files
+        .iter()
+        // custom installs were put there by the user, they're hardly news
+        .filter(|file| !file.md5().is_empty())
+        .filter_map(|file| {
+            let change = match seen_by_id.get(file.id()) {
+                None => MapChange::Added,
+                Some(seen) if seen.md5() != file.md5() || seen.date() != file.date() => {
+                    MapChange::Updated
+                }
+                Some(seen) if seen.rating() != file.rating() => MapChange::Rerated,
+                Some(_) => return None,
+            };
+            Some((file.id().to_owned(), change))
+        })
+        .collect()
+}
@@ -0,0 +1,167 @@
+//! A stand-in for quaddicted.com serving the files in `tests/fixtures`, plus the temp dirs an
+//! install needs.
+#![allow(dead_code)]
+
+use rq_injector_core::configuration::Mirror;
+use rq_injector_core::download_progress::DownloadProgress;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tempfile::TempDir;
+
+pub fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+pub struct MockServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Couldn't bind the mock server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let thread_requests = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => handle_request(stream, &thread_requests),
+                    Err(_) => break,
+                }
+            }
+        });
+        Self { base_url, requests }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn mirror(&self) -> Mirror {
+        Mirror::from_base_url("mock", &self.base_url)
+    }
+
+    /// Every path asked for so far, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Nothing listens on port 1, so requests to this fail straight away.
+pub fn dead_mirror() -> Mirror {
+    Mirror::from_base_url("dead", "http://127.0.0.1:1")
+}
+
+fn handle_request(stream: TcpStream, requests: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_owned();
+    let mut if_none_match = None;
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => (),
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("if-none-match") {
+                if_none_match = Some(value.trim().to_owned());
+            }
+        }
+    }
+    requests.lock().unwrap().push(path.clone());
+
+    let body = route(&path).and_then(|file_name| fs::read(fixture_path(&file_name)).ok());
+    let (status, headers, body) = match body {
+        Some(body) => {
+            let etag = format!("\"{:x}\"", md5::compute(&body));
+            match if_none_match.as_ref() == Some(&etag) {
+                true => ("304 Not Modified", format!("ETag: {}\r\n", etag), vec![]),
+                false => {
+                    let headers = format!("Content-Length: {}\r\nETag: {}\r\n", body.len(), etag);
+                    ("200 OK", headers, body)
+                }
+            }
+        }
+        None => (
+            "404 Not Found",
+            String::from("Content-Length: 0\r\n"),
+            vec![],
+        ),
+    };
+    let mut response = format!(
+        "HTTP/1.1 {}\r\n{}Connection: close\r\n\r\n",
+        status, headers
+    )
+    .into_bytes();
+    response.extend(body);
+    let mut stream = reader.into_inner();
+    let _ = stream.write_all(&response);
+}
+
+// the same layout as quaddicted.com, all served out of the one fixtures dir
+fn route(path: &str) -> Option<String> {
+    let file_name = match path {
+        "/reviews/quaddicted_database.xml" => "quaddicted_database.xml",
+        _ => path
+            .strip_prefix("/filebase/")
+            .or_else(|| path.strip_prefix("/reviews/screenshots/"))?,
+    };
+    match file_name.contains('/') || file_name.contains("..") {
+        true => None,
+        false => Some(file_name.to_owned()),
+    }
+}
+
+pub struct TestDirs {
+    _root: TempDir,
+    pub config_dir: PathBuf,
+    pub download_dir: PathBuf,
+    pub quake_dir: PathBuf,
+    pub image_dir: PathBuf,
+}
+
+impl TestDirs {
+    pub fn new() -> Self {
+        let root = tempfile::tempdir().expect("Couldn't make a temp dir");
+        let make_dir = |name: &str| {
+            let dir = root.path().join(name);
+            fs::create_dir_all(&dir).unwrap();
+            dir
+        };
+        Self {
+            config_dir: make_dir("config"),
+            download_dir: make_dir("downloads"),
+            quake_dir: make_dir("quake"),
+            image_dir: make_dir("images"),
+            _root: root,
+        }
+    }
+
+    pub fn download_dir_string(&self) -> String {
+        self.download_dir.to_string_lossy().to_string()
+    }
+
+    pub fn quake_dir_string(&self) -> String {
+        self.quake_dir.to_string_lossy().to_string()
+    }
+}
+
+/// For installs whose progress nobody is watching.
+pub fn ignore_progress(_: DownloadProgress) {}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::TestDirs;
+use rq_injector_core::configuration::{LocalMaps, MapPack, MapPackBuilder};
+use std::fs;
+
+fn map_pack(id: &str, title: &str) -> MapPack {
+    MapPackBuilder::default()
+        .id(id.to_owned())
+        .files(vec![])
+        .title(title.to_owned())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn a_map_is_only_listed_once() {
+    let dirs = TestDirs::new();
+    let mut local_maps = LocalMaps::new(dirs.config_dir.clone());
+    local_maps.add_map(map_pack("testdep", "first"));
+    local_maps.add_map(map_pack("testdep", "second"));
+    assert_eq!(local_maps.maps().len(), 1);
+    assert_eq!(local_maps.maps()[0].title(), "first");
+}
+
+#[test]
+fn unreadable_local_maps_are_backed_up() {
+    let dirs = TestDirs::new();
+    let contents = r#"<LocalMaps><map><file crc="not a number" name="x"/></map></LocalMaps>"#;
+    let path = dirs.config_dir.join("installedMaps.xml");
+    fs::write(&path, contents).unwrap();
+
+    let local_maps = LocalMaps::new(dirs.config_dir.clone());
+    assert!(local_maps.maps().is_empty());
+    assert!(!path.exists());
+    let backups: Vec<String> = fs::read_dir(&dirs.config_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with("installedMaps.xml.") && name.ends_with(".bak"))
+        .collect();
+    assert_eq!(backups.len(), 1);
+    let backup = fs::read_to_string(dirs.config_dir.join(&backups[0])).unwrap();
+    assert_eq!(backup, contents);
+}
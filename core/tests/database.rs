@@ -0,0 +1,104 @@
+mod common;
+
+use common::{dead_mirror, fixture_path, MockServer, TestDirs};
+use rq_injector_core::configuration::DatabaseMeta;
+use rq_injector_core::database_refresh::{is_database_stale, refresh_database, DatabaseRefresh};
+use rq_injector_core::quake_file::{
+    initialize_data, read_database_file, DatabaseError, BACKUP_FILE_NAME, DATABASE_FILE_NAME,
+};
+use std::fs;
+
+#[test]
+fn first_run_fetches_the_database() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let (files, error) = initialize_data(dirs.config_dir.clone(), false, &[server.mirror()]);
+    assert!(error.is_none());
+    let ids: Vec<&String> = files.files().iter().map(|file| file.id()).collect();
+    assert_eq!(ids, vec!["testdep", "testmap"]);
+    assert!(dirs.config_dir.join(DATABASE_FILE_NAME).is_file());
+    assert!(dirs.config_dir.join(BACKUP_FILE_NAME).is_file());
+    let meta = DatabaseMeta::new(dirs.config_dir.clone());
+    assert!(!meta.etag().is_empty());
+}
+
+#[test]
+fn a_local_database_is_used_without_asking_the_server() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    fs::copy(
+        fixture_path("quaddicted_database.xml"),
+        dirs.config_dir.join(DATABASE_FILE_NAME),
+    )
+    .unwrap();
+    let (files, error) = initialize_data(dirs.config_dir.clone(), false, &[server.mirror()]);
+    assert!(error.is_none());
+    assert_eq!(files.files().len(), 2);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn refresh_reports_unchanged_when_the_etag_matches() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let mirrors = [server.mirror()];
+    match refresh_database(&dirs.config_dir, &mirrors).unwrap() {
+        DatabaseRefresh::Updated(diff) => assert_eq!(diff.added().len(), 2),
+        DatabaseRefresh::Unchanged => panic!("The first fetch should be an update"),
+    }
+    match refresh_database(&dirs.config_dir, &mirrors).unwrap() {
+        DatabaseRefresh::Unchanged => (),
+        DatabaseRefresh::Updated(_) => panic!("The server sent the same database again"),
+    }
+}
+
+#[test]
+fn a_failing_mirror_falls_over_to_the_next() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let mirrors = [dead_mirror(), server.mirror()];
+    let (files, error) = initialize_data(dirs.config_dir.clone(), false, &mirrors);
+    assert!(error.is_none());
+    assert_eq!(files.files().len(), 2);
+}
+
+#[test]
+fn offline_without_a_database_is_an_error() {
+    let dirs = TestDirs::new();
+    let (files, error) = initialize_data(dirs.config_dir.clone(), true, &[]);
+    assert!(files.files().is_empty());
+    assert!(matches!(error, Some(DatabaseError::Offline)));
+}
+
+#[test]
+fn a_corrupt_database_falls_back_to_the_backup() {
+    let dirs = TestDirs::new();
+    let database_path = dirs.config_dir.join(DATABASE_FILE_NAME);
+    fs::write(&database_path, "<files><file id=").unwrap();
+    fs::copy(
+        fixture_path("quaddicted_database.xml"),
+        dirs.config_dir.join(BACKUP_FILE_NAME),
+    )
+    .unwrap();
+    let (files, error) = initialize_data(dirs.config_dir.clone(), false, &[dead_mirror()]);
+    assert!(matches!(error, Some(DatabaseError::Unavailable(_))));
+    assert_eq!(files.files().len(), 2);
+    assert!(dirs.config_dir.join("database.xml.corrupt").is_file());
+    assert!(read_database_file(&database_path).is_none());
+}
+
+#[test]
+fn only_an_old_database_is_stale() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    assert!(!is_database_stale(&dirs.config_dir, 24));
+    refresh_database(&dirs.config_dir, &[server.mirror()]).unwrap();
+    assert!(!is_database_stale(&dirs.config_dir, 24));
+
+    let mut meta = DatabaseMeta::new(dirs.config_dir.clone());
+    meta.set_fetched_at(meta.fetched_at() - 25 * 3600);
+    meta.write_to_file(dirs.config_dir.clone());
+    assert!(is_database_stale(&dirs.config_dir, 24));
+    // zero turns the refresh off
+    assert!(!is_database_stale(&dirs.config_dir, 0));
+}
@@ -0,0 +1,85 @@
+use rq_injector_core::download_queue::{DownloadQueue, QueueState};
+use std::sync::atomic::Ordering;
+
+fn id(map_id: &str) -> String {
+    String::from(map_id)
+}
+
+fn started(queue: &mut DownloadQueue) -> Option<String> {
+    queue.start_next().map(|item| item.map_id().to_owned())
+}
+
+#[test]
+fn maps_sharing_a_dependency_wait_for_each_other() {
+    let mut queue = DownloadQueue::new(3);
+    assert!(queue.enqueue(&id("map1"), vec![id("quoth")]));
+    assert!(queue.enqueue(&id("quoth"), vec![]));
+    assert!(queue.enqueue(&id("map2"), vec![id("quoth")]));
+    assert!(queue.enqueue(&id("map3"), vec![]));
+    // already waiting
+    assert!(!queue.enqueue(&id("map1"), vec![id("quoth")]));
+
+    assert_eq!(started(&mut queue), Some(id("map1")));
+    // quoth and map2 both need what map1 is fetching
+    assert_eq!(started(&mut queue), Some(id("map3")));
+    assert_eq!(started(&mut queue), None);
+
+    queue.finish(&id("map1"), QueueState::Done);
+    assert_eq!(started(&mut queue), Some(id("quoth")));
+    assert_eq!(started(&mut queue), None);
+    queue.finish(&id("quoth"), QueueState::Done);
+    assert_eq!(started(&mut queue), Some(id("map2")));
+}
+
+#[test]
+fn a_full_queue_waits_for_a_free_slot() {
+    let mut queue = DownloadQueue::new(1);
+    queue.enqueue(&id("map1"), vec![]);
+    queue.enqueue(&id("map2"), vec![]);
+    assert_eq!(started(&mut queue), Some(id("map1")));
+    assert_eq!(started(&mut queue), None);
+    queue.finish(&id("map1"), QueueState::Done);
+    assert_eq!(started(&mut queue), Some(id("map2")));
+}
+
+#[test]
+fn cancelling_a_waiting_map_takes_it_out_of_the_running() {
+    let mut queue = DownloadQueue::new(1);
+    queue.enqueue(&id("map1"), vec![]);
+    queue.enqueue(&id("map2"), vec![]);
+    queue.enqueue(&id("map3"), vec![]);
+    let running = queue.start_next().unwrap();
+
+    queue.cancel(&id("map2"));
+    assert_eq!(
+        queue.get_item(&id("map2")).unwrap().state(),
+        &QueueState::Cancelled
+    );
+    // a running install is only asked to stop
+    queue.cancel(&id("map1"));
+    assert_eq!(
+        queue.get_item(&id("map1")).unwrap().state(),
+        &QueueState::Running
+    );
+    assert!(running.cancel_flag().load(Ordering::Relaxed));
+
+    queue.finish(&id("map1"), QueueState::Cancelled);
+    assert_eq!(started(&mut queue), Some(id("map3")));
+}
+
+#[test]
+fn a_failed_map_can_be_retried() {
+    let mut queue = DownloadQueue::new(1);
+    queue.enqueue(&id("map1"), vec![id("quoth")]);
+    // nothing to retry while it's still going
+    assert!(!queue.retry(&id("map1")));
+    queue.start_next();
+    queue.finish(&id("map1"), QueueState::Failed(String::from("no mirror")));
+    assert_eq!(started(&mut queue), None);
+
+    assert!(queue.retry(&id("map1")));
+    let item = queue.start_next().unwrap();
+    assert_eq!(item.map_id(), "map1");
+    assert_eq!(item.dependencies(), &vec![id("quoth")]);
+    assert!(!queue.retry(&id("unknown")));
+}
@@ -0,0 +1,56 @@
+#![cfg(unix)]
+
+use rq_injector_core::game_player::{session_log_path, GameOutput, GamePlayerBuilder};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+#[test]
+fn output_is_streamed_and_logged_with_the_exit_code() {
+    let logs_dir = TempDir::new().unwrap();
+    let game_player = GamePlayerBuilder::default()
+        .quake_exe(String::from("sh"))
+        .working_dir(String::new())
+        .map_id(String::from("testmap"))
+        .args(vec![
+            String::from("-c"),
+            String::from("echo out; echo err 1>&2; exit 3"),
+        ])
+        .build()
+        .unwrap();
+    let log_path = session_log_path(logs_dir.path(), "testmap");
+    let outputs = Arc::new(Mutex::new(vec![]));
+    let sink = outputs.clone();
+    let game = game_player
+        .launch(Some(&log_path), move |output| {
+            sink.lock().unwrap().push(output)
+        })
+        .unwrap();
+    assert_eq!(game.wait(), Some(3));
+
+    let outputs = outputs.lock().unwrap();
+    assert!(outputs.contains(&GameOutput::Stdout(String::from("out"))));
+    assert!(outputs.contains(&GameOutput::Stderr(String::from("err"))));
+    // nothing comes after the exit
+    assert_eq!(outputs.last(), Some(&GameOutput::Exited(Some(3))));
+
+    let log = fs::read_to_string(&log_path).unwrap();
+    assert!(log.starts_with("sh -c"));
+    assert!(log.contains("out\n"));
+    assert!(log.contains("err\n"));
+    assert!(log.ends_with("exit code: Some(3)\n"));
+}
+
+#[test]
+fn a_killed_game_has_no_exit_code() {
+    let game_player = GamePlayerBuilder::default()
+        .quake_exe(String::from("sleep"))
+        .working_dir(String::new())
+        .map_id(String::from("testmap"))
+        .args(vec![String::from("30")])
+        .build()
+        .unwrap();
+    let game = game_player.launch(None, |_| ()).unwrap();
+    game.kill();
+    assert_eq!(game.wait(), None);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{fixture_path, MockServer, TestDirs};
+use rq_injector_core::image_loader::ImageLoader;
+use std::fs;
+use std::path::PathBuf;
+
+fn not_found_image() -> PathBuf {
+    PathBuf::from("resources/images/not_found.png")
+}
+
+#[test]
+fn screenshots_are_fetched_into_the_cache() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let mut image_loader = ImageLoader::new(String::from("testmap"), String::from("0"));
+    image_loader.load_map_image(false, dirs.image_dir.clone(), &[server.mirror()]);
+    assert_eq!(image_loader.path(), &dirs.image_dir.join("testmap.jpg"));
+    assert_eq!(
+        fs::read(image_loader.path()).unwrap(),
+        fs::read(fixture_path("testmap.jpg")).unwrap()
+    );
+
+    // the second look comes out of the cache
+    let mut image_loader = ImageLoader::new(String::from("testmap"), String::from("0"));
+    image_loader.load_map_image(false, dirs.image_dir.clone(), &[server.mirror()]);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn a_missing_screenshot_falls_back_to_not_found() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let mut image_loader = ImageLoader::new(String::from("testdep"), String::from("0"))
+        .with_not_found_image(not_found_image());
+    image_loader.load_map_image(false, dirs.image_dir.clone(), &[server.mirror()]);
+    assert_eq!(image_loader.path(), &not_found_image());
+    assert!(!dirs.image_dir.join("testdep.jpg").exists());
+}
+
+#[test]
+fn offline_never_asks_the_server() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let mut image_loader = ImageLoader::new(String::from("testmap"), String::from("0"))
+        .with_not_found_image(not_found_image());
+    image_loader.load_map_image(true, dirs.image_dir.clone(), &[server.mirror()]);
+    assert_eq!(image_loader.path(), &not_found_image());
+    assert!(server.requests().is_empty());
+}
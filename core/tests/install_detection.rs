@@ -0,0 +1,121 @@
+use rq_injector_core::install_detection::{
+    detect_installs, is_quake_dir, parse_library_folders, InstallSource, SearchPaths,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn make_quake_dir(dir: &Path, pak_name: &str) {
+    let id1 = dir.join("id1");
+    fs::create_dir_all(&id1).unwrap();
+    fs::write(id1.join(pak_name), b"PACK").unwrap();
+}
+
+#[test]
+fn library_folders_are_read_from_both_layouts() {
+    let new_layout = r#"
+"libraryfolders"
+{
+    "0"
+    {
+        "path"      "/home/ranger/.local/share/Steam"
+        "label"     ""
+        "apps"
+        {
+            "2310"      "734003200"
+        }
+    }
+    "1"
+    {
+        "path"      "D:\\SteamLibrary"
+    }
+}"#;
+    assert_eq!(
+        parse_library_folders(new_layout),
+        vec![
+            PathBuf::from("/home/ranger/.local/share/Steam"),
+            PathBuf::from(r"D:\SteamLibrary"),
+        ]
+    );
+
+    let old_layout = r#"
+"LibraryFolders"
+{
+    "TimeNextStatsReport"   "1618000000"
+    "ContentStatsID"        "-4242"
+    "1"     "/mnt/games/SteamLibrary"
+}"#;
+    assert_eq!(
+        parse_library_folders(old_layout),
+        vec![PathBuf::from("/mnt/games/SteamLibrary")]
+    );
+}
+
+#[test]
+fn only_dirs_with_pak0_count() {
+    let dir = TempDir::new().unwrap();
+    assert!(!is_quake_dir(dir.path()));
+    fs::create_dir(dir.path().join("id1")).unwrap();
+    assert!(!is_quake_dir(dir.path()));
+
+    let dos_dir = TempDir::new().unwrap();
+    make_quake_dir(dos_dir.path(), "PAK0.PAK");
+    assert!(is_quake_dir(dos_dir.path()));
+}
+
+#[test]
+fn steam_libraries_are_searched_and_paired_with_engines() {
+    let steam_root = TempDir::new().unwrap();
+    let library = TempDir::new().unwrap();
+    let quake_dir = library.path().join("steamapps/common/Quake");
+    make_quake_dir(&quake_dir, "pak0.pak");
+    fs::create_dir_all(steam_root.path().join("steamapps")).unwrap();
+    let vdf = format!(
+        "\"libraryfolders\" {{ \"1\" {{ \"path\" \"{}\" }} }}",
+        library.path().display()
+    );
+    fs::write(steam_root.path().join("steamapps/libraryfolders.vdf"), vdf).unwrap();
+
+    let bin_dir = TempDir::new().unwrap();
+    let engine = bin_dir
+        .path()
+        .join(format!("quakespasm{}", std::env::consts::EXE_SUFFIX));
+    fs::write(&engine, b"").unwrap();
+    // not a quake dir, so it's left out
+    let empty_dir = TempDir::new().unwrap();
+
+    let search = SearchPaths {
+        steam_roots: vec![steam_root.path().to_owned()],
+        gog_dirs: vec![empty_dir.path().to_owned()],
+        path_dirs: vec![bin_dir.path().to_owned()],
+        ..Default::default()
+    };
+    let installs = detect_installs(&search);
+    assert_eq!(installs.len(), 1);
+    assert_eq!(installs[0].quake_dir(), &quake_dir);
+    assert_eq!(installs[0].quake_exe(), &Some(engine));
+    assert_eq!(installs[0].source(), &InstallSource::Steam);
+}
+
+#[test]
+fn heroic_installs_come_from_its_manifest() {
+    let quake_dir = TempDir::new().unwrap();
+    make_quake_dir(quake_dir.path(), "pak0.pak");
+    let config = TempDir::new().unwrap();
+    let manifest = config.path().join("installed.json");
+    let json = format!(
+        r#"{{"installed": [{{"appName": "1435828198", "install_path": "{}"}}]}}"#,
+        quake_dir.path().display()
+    );
+    fs::write(&manifest, json).unwrap();
+
+    let search = SearchPaths {
+        heroic_manifests: vec![manifest],
+        ..Default::default()
+    };
+    let installs = detect_installs(&search);
+    assert_eq!(installs.len(), 1);
+    assert_eq!(installs[0].quake_dir(), &quake_dir.path().to_owned());
+    assert_eq!(installs[0].quake_exe(), &None);
+    assert_eq!(installs[0].source(), &InstallSource::Heroic);
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use common::{fixture_path, ignore_progress, MockServer, TestDirs};
+use rq_injector_core::configuration::{Dependency, LocalMaps, MapPack};
+use rq_injector_core::dependencies::resolve_dependencies;
+use rq_injector_core::installer::{open_archive, Installer};
+use rq_injector_core::quake_file::{read_database_file, QuakeFile};
+use std::fs;
+
+fn fixture_files() -> Vec<QuakeFile> {
+    read_database_file(&fixture_path("quaddicted_database.xml")).expect("Fixture won't parse")
+}
+
+fn install_testmap(server: &MockServer, dirs: &TestDirs) -> Installer {
+    let files = fixture_files();
+    let map_id = String::from("testmap");
+    let file = files.iter().find(|f| f.id() == &map_id).unwrap();
+    let local_maps = LocalMaps::new(dirs.config_dir.clone());
+    let dependencies = resolve_dependencies(&map_id, &files, &local_maps).unwrap();
+    let mut installer = Installer::new()
+        .with_download_dir(dirs.download_dir_string())
+        .with_quake_dir(dirs.quake_dir_string())
+        .with_map_id(map_id)
+        .with_md5(file.md5().to_owned())
+        .with_zip_base_dir(file.tech_info().zip_base_dir().clone())
+        .with_dependencies(dependencies)
+        .with_mirrors(vec![server.mirror()]);
+    installer
+        .install_map(&ignore_progress)
+        .expect("Install failed");
+    installer
+}
+
+fn uninstaller(dirs: &TestDirs, map_pack: &MapPack) -> Installer {
+    Installer::new()
+        .with_download_dir(dirs.download_dir_string())
+        .with_quake_dir(dirs.quake_dir_string())
+        .with_map_id(map_pack.id().to_owned())
+        .with_installed_map_pack(Some(map_pack.clone()))
+}
+
+#[test]
+fn install_map_pulls_in_its_dependencies() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let installer = install_testmap(&server, &dirs);
+
+    assert!(dirs.quake_dir.join("testmap/maps/testmap.bsp").is_file());
+    assert!(dirs.quake_dir.join("testmap/readme.txt").is_file());
+    assert!(dirs.quake_dir.join("testdep/pak0.pak").is_file());
+    assert!(dirs.download_dir.join("testmap.zip").is_file());
+    assert!(dirs.download_dir.join("testdep.zip").is_file());
+
+    let map_pack = installer.installed_map_pack().clone().unwrap();
+    assert_eq!(
+        map_pack.dependencies(),
+        &vec![Dependency::new(String::from("testdep"))]
+    );
+    assert!(!*map_pack.is_dependency());
+    let dependency = &installer.installed_dependencies()[0];
+    assert_eq!(dependency.id(), "testdep");
+    assert!(*dependency.is_dependency());
+    assert!(server
+        .requests()
+        .contains(&String::from("/filebase/testmap.zip")));
+}
+
+#[test]
+fn cached_zips_are_not_downloaded_again() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    install_testmap(&server, &dirs);
+    let request_count = server.requests().len();
+    fs::remove_dir_all(dirs.quake_dir.join("testmap")).unwrap();
+    install_testmap(&server, &dirs);
+    assert_eq!(server.requests().len(), request_count);
+    assert!(dirs.quake_dir.join("testmap/maps/testmap.bsp").is_file());
+}
+
+#[test]
+fn installed_maps_survive_a_restart() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let installer = install_testmap(&server, &dirs);
+    let map_pack = installer.installed_map_pack().clone().unwrap();
+
+    let mut local_maps = LocalMaps::new(dirs.config_dir.clone());
+    for dependency in installer.installed_dependencies() {
+        local_maps.add_map(dependency.clone());
+    }
+    local_maps.add_map(map_pack.clone());
+    local_maps.write_to_file(dirs.config_dir.clone());
+
+    let reloaded = LocalMaps::new(dirs.config_dir.clone());
+    assert!(reloaded.is_map_installed(&String::from("testdep")));
+    let reloaded_pack = reloaded.get_map(&String::from("testmap")).unwrap();
+    assert_eq!(reloaded_pack.dependencies(), map_pack.dependencies());
+    let names = |pack: &MapPack| {
+        pack.files()
+            .iter()
+            .map(|file| (file.name().to_owned(), *file.crc()))
+            .collect::<Vec<(String, u32)>>()
+    };
+    assert_eq!(names(reloaded_pack), names(&map_pack));
+}
+
+#[test]
+fn uninstall_removes_the_map_dir() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let installer = install_testmap(&server, &dirs);
+    let map_pack = installer.installed_map_pack().clone().unwrap();
+
+    let kept = uninstaller(&dirs, &map_pack).uninstall_map(false);
+    assert!(kept.is_empty());
+    assert!(!dirs.quake_dir.join("testmap").exists());
+    // dependencies are uninstalled on their own
+    assert!(dirs.quake_dir.join("testdep/pak0.pak").is_file());
+}
+
+#[test]
+fn uninstall_keeps_files_changed_after_install() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    let installer = install_testmap(&server, &dirs);
+    let map_pack = installer.installed_map_pack().clone().unwrap();
+    let readme_path = dirs.quake_dir.join("testmap/readme.txt");
+    fs::write(&readme_path, "my notes").unwrap();
+
+    let kept = uninstaller(&dirs, &map_pack).uninstall_map(false);
+    assert_eq!(kept, vec![String::from("testmap/readme.txt")]);
+    assert!(readme_path.is_file());
+    assert!(!dirs.quake_dir.join("testmap/maps/testmap.bsp").exists());
+
+    uninstaller(&dirs, &map_pack).uninstall_map(true);
+    assert!(!dirs.quake_dir.join("testmap").exists());
+}
+
+#[test]
+fn local_archives_that_dont_match_the_database_are_ignored() {
+    let server = MockServer::start();
+    let dirs = TestDirs::new();
+    fs::write(dirs.download_dir.join("testmap.bsp"), "not the release").unwrap();
+    install_testmap(&server, &dirs);
+    assert!(server
+        .requests()
+        .contains(&String::from("/filebase/testmap.zip")));
+    assert_ne!(
+        fs::read(dirs.quake_dir.join("testmap/maps/testmap.bsp")).unwrap(),
+        b"not the release"
+    );
+}
+
+#[test]
+fn a_lone_pak_gets_no_autoexec() {
+    let dirs = TestDirs::new();
+    let pak_path = dirs.download_dir.join("mypak.pak");
+    fs::write(&pak_path, "PACK").unwrap();
+    let mut installer = Installer::new()
+        .with_download_dir(dirs.download_dir_string())
+        .with_quake_dir(dirs.quake_dir_string())
+        .with_map_id(String::from("mypak"));
+    let source = pak_path.to_string_lossy().to_string();
+    installer
+        .install_custom(&source, &String::from("My pak"), &ignore_progress)
+        .expect("Install failed");
+    assert!(dirs.quake_dir.join("mypak/pak0.pak").is_file());
+    assert!(!dirs.quake_dir.join("mypak/autoexec.cfg").exists());
+}
+
+#[test]
+fn a_lone_pak_leaves_existing_paks_alone() {
+    let dirs = TestDirs::new();
+    fs::create_dir_all(dirs.quake_dir.join("mypak")).unwrap();
+    fs::write(dirs.quake_dir.join("mypak/pak0.pak"), "PACK theirs").unwrap();
+    let pak_path = dirs.download_dir.join("mypak.pak");
+    fs::write(&pak_path, "PACK ours").unwrap();
+    let mut installer = Installer::new()
+        .with_download_dir(dirs.download_dir_string())
+        .with_quake_dir(dirs.quake_dir_string())
+        .with_map_id(String::from("mypak"));
+    let source = pak_path.to_string_lossy().to_string();
+    installer
+        .install_custom(&source, &String::from("My pak"), &ignore_progress)
+        .expect("Install failed");
+    assert_eq!(
+        fs::read(dirs.quake_dir.join("mypak/pak0.pak")).unwrap(),
+        b"PACK theirs"
+    );
+    assert_eq!(
+        fs::read(dirs.quake_dir.join("mypak/pak1.pak")).unwrap(),
+        b"PACK ours"
+    );
+
+    // a repair writes over its own pak rather than adding another
+    installer
+        .repair_map(&ignore_progress)
+        .expect("Repair failed");
+    assert!(!dirs.quake_dir.join("mypak/pak2.pak").exists());
+}
+
+#[test]
+fn seven_zip_archives_are_read() {
+    let dirs = TestDirs::new();
+    let release_dir = dirs.download_dir.join("release");
+    fs::create_dir_all(release_dir.join("maps")).unwrap();
+    fs::write(release_dir.join("maps/test.bsp"), "bsp").unwrap();
+    let archive_path = dirs.download_dir.join("release.7z");
+    sevenz_rust::compress_to_path(&release_dir, &archive_path).unwrap();
+
+    let mut archive = open_archive(&archive_path).unwrap();
+    assert_eq!(archive.entry_names().unwrap(), vec!["maps/test.bsp"]);
+    let mut entries = vec![];
+    archive
+        .for_each_entry(&mut |entry, reader| {
+            let mut contents = String::new();
+            reader.read_to_string(&mut contents)?;
+            entries.push((entry.name.clone(), entry.count, contents));
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(
+        entries,
+        vec![(String::from("maps/test.bsp"), 1, String::from("bsp"))]
+    );
+}
@@ -0,0 +1,140 @@
+use rq_injector_core::configuration::EngineProfile;
+use rq_injector_core::launch_args::{join_args, split_args, LaunchArgsBuilder, LaunchArgsError};
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn engine() -> EngineProfile {
+    let mut engine = EngineProfile::new("quakespasm", "quakespasm");
+    engine.set_quake_dir(String::from("/quake"));
+    engine
+}
+
+#[test]
+fn quoted_arguments_stay_together() {
+    assert_eq!(
+        split_args(r#"-game  foo +exec "my config.cfg" '+set name "bob"' C:\quake\id1"#).unwrap(),
+        strings(&[
+            "-game",
+            "foo",
+            "+exec",
+            "my config.cfg",
+            r#"+set name "bob""#,
+            r"C:\quake\id1"
+        ])
+    );
+    assert_eq!(split_args(r"a\ b").unwrap(), strings(&["a b"]));
+    assert_eq!(split_args("  ").unwrap(), Vec::<String>::new());
+    assert_eq!(split_args("''").unwrap(), strings(&[""]));
+}
+
+#[test]
+fn unclosed_quotes_are_an_error() {
+    assert_eq!(
+        split_args(r#"-game "foo"#),
+        Err(LaunchArgsError::UnclosedQuote {
+            quote: '"',
+            line: String::from(r#"-game "foo"#)
+        })
+    );
+}
+
+#[test]
+fn joined_args_split_back_the_same() {
+    let args = strings(&["quakespasm", "+exec", "my config.cfg", "it's", ""]);
+    assert_eq!(split_args(&join_args(&args)).unwrap(), args);
+}
+
+#[test]
+fn later_layers_override_game_and_map() {
+    let launch_args = LaunchArgsBuilder::default()
+        .engine(engine())
+        .map_id(String::from("testmap"))
+        .database_line(Some(String::from("-hipnotic -game testmap +map start")))
+        .user_options(String::from("-heapsize 65536 +map e1m1"))
+        .map_options(String::from("-game other +skill 2"))
+        .build()
+        .unwrap();
+    assert_eq!(
+        launch_args.to_argv().unwrap(),
+        strings(&[
+            "-basedir",
+            "/quake",
+            "-game",
+            "other",
+            "-hipnotic",
+            "-heapsize",
+            "65536",
+            "+skill",
+            "2",
+            "+map",
+            "e1m1"
+        ])
+    );
+}
+
+#[test]
+fn chosen_start_map_wins() {
+    let launch_args = LaunchArgsBuilder::default()
+        .engine(engine())
+        .map_id(String::from("testmap"))
+        .start_map(Some(String::from("testmap2")))
+        .map_options(String::from("+map testmap1"))
+        .build()
+        .unwrap();
+    assert_eq!(
+        launch_args.to_argv().unwrap(),
+        strings(&["-basedir", "/quake", "-game", "testmap", "+map", "testmap2"])
+    );
+}
+
+#[test]
+fn engine_quirks_shape_the_flags() {
+    let mut engine = engine();
+    engine.set_game_flag(String::from("+gamedir"));
+    engine.set_uses_basedir(false);
+    engine.set_launch_options(String::from("-window"));
+    let launch_args = LaunchArgsBuilder::default()
+        .engine(engine)
+        .map_id(String::from("testmap"))
+        .database_line(Some(String::from("-game testdep")))
+        .build()
+        .unwrap();
+    assert_eq!(
+        launch_args.to_argv().unwrap(),
+        strings(&["+gamedir", "testdep", "-window"])
+    );
+}
+
+#[test]
+fn user_basedir_passes_through_when_the_engine_takes_none() {
+    let mut engine = engine();
+    engine.set_uses_basedir(false);
+    let launch_args = LaunchArgsBuilder::default()
+        .engine(engine)
+        .map_id(String::from("testmap"))
+        .user_options(String::from("-basedir /elsewhere -window"))
+        .build()
+        .unwrap();
+    assert_eq!(
+        launch_args.to_argv().unwrap(),
+        strings(&["-game", "testmap", "-basedir", "/elsewhere", "-window"])
+    );
+}
+
+#[test]
+fn empty_game_flag_is_left_out() {
+    let mut engine = engine();
+    engine.set_game_flag(String::new());
+    let launch_args = LaunchArgsBuilder::default()
+        .engine(engine)
+        .map_id(String::from("testmap"))
+        .map_options(String::from("+skill 2"))
+        .build()
+        .unwrap();
+    assert_eq!(
+        launch_args.to_argv().unwrap(),
+        strings(&["-basedir", "/quake", "+skill", "2"])
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::ignore_progress;
+use rq_injector_core::configuration::Mirror;
+use rq_injector_core::download_error::DownloadError;
+use rq_injector_core::http::{configure, NetworkSettings};
+use rq_injector_core::request_utils::{get_database_if_changed, get_file_from_url};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn a_server_that_never_answers_times_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mirror = Mirror::from_base_url(
+        "silent",
+        &format!("http://{}", listener.local_addr().unwrap()),
+    );
+    // takes the connection and then says nothing
+    thread::spawn(move || {
+        let _streams: Vec<_> = listener.incoming().collect();
+    });
+    configure(&NetworkSettings {
+        read_timeout_secs: 1,
+        ..Default::default()
+    });
+    let dir = tempfile::tempdir().unwrap();
+
+    let started = Instant::now();
+    let result = get_database_if_changed(dir.path().join("database.xml"), "", "", &[mirror]);
+    match result {
+        Err(DownloadError::Io(e)) => assert_eq!(e.kind(), ErrorKind::TimedOut),
+        Err(e) => panic!("Expected a timeout, got {}", e),
+        Ok(_) => panic!("Expected a timeout"),
+    }
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn a_download_path_without_a_file_name_is_an_error() {
+    // nothing listens there, the path is rejected before any request goes out
+    let url = String::from("http://127.0.0.1:1/map.zip");
+    let result = get_file_from_url(&url, "/", &ignore_progress, &AtomicBool::new(false));
+    match result {
+        Err(DownloadError::Io(e)) => assert_eq!(e.kind(), ErrorKind::InvalidInput),
+        Err(e) => panic!("Expected an invalid path, got {}", e),
+        Ok(_) => panic!("Expected an invalid path"),
+    }
+}
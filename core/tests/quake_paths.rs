@@ -0,0 +1,107 @@
+use rq_injector_core::quake_file::{read_database_file, QuakeFile};
+use rq_injector_core::quake_paths::{
+    required_mission_packs, validate_dir, validate_quake_dir, validate_quake_exe, MissionPack,
+    PathError,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn make_pak(dir: &Path, pack_dir: &str) {
+    fs::create_dir_all(dir.join(pack_dir)).unwrap();
+    fs::write(dir.join(pack_dir).join("pak0.pak"), b"PACK").unwrap();
+}
+
+fn database_files(techinfos: &[(&str, &str)]) -> Vec<QuakeFile> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<files>\n");
+    for (id, techinfo) in techinfos {
+        xml.push_str(&format!(
+            "<file id=\"{}\" type=\"1\" rating=\"3\"><author>a</author><title>t</title>\
+             <md5sum>0</md5sum><size>0</size><date>01.01.2020</date>\
+             <description>d</description><techinfo>{}</techinfo></file>\n",
+            id, techinfo
+        ));
+    }
+    xml.push_str("</files>\n");
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("database.xml");
+    fs::write(&path, xml).unwrap();
+    read_database_file(&path).unwrap()
+}
+
+#[test]
+fn mission_packs_are_found_by_their_pak_files() {
+    let quake_dir = TempDir::new().unwrap();
+    // a directory on its own isn't an install
+    fs::create_dir(quake_dir.path().join("rogue")).unwrap();
+    make_pak(quake_dir.path(), "HIPNOTIC");
+    assert!(MissionPack::Hipnotic.is_installed(quake_dir.path()));
+    assert!(!MissionPack::Rogue.is_installed(quake_dir.path()));
+    assert!(!MissionPack::Hipnotic.is_installed(""));
+}
+
+#[test]
+fn mission_packs_are_read_from_command_lines_and_requirements() {
+    let files = database_files(&[
+        ("flag", "<commandline>-hipnotic -game flag</commandline>"),
+        ("game", "<commandline>-game rogue +map start</commandline>"),
+        (
+            "required",
+            "<requirements><file id=\"hipnotic\" /></requirements>",
+        ),
+        ("plain", "<commandline>-game plain</commandline>"),
+    ]);
+    let packs: Vec<Vec<MissionPack>> = files.iter().map(required_mission_packs).collect();
+    assert_eq!(
+        packs,
+        vec![
+            vec![MissionPack::Hipnotic],
+            vec![MissionPack::Rogue],
+            vec![MissionPack::Hipnotic],
+            vec![],
+        ]
+    );
+}
+
+#[test]
+fn paths_are_checked_for_what_they_should_hold() {
+    let quake_dir = TempDir::new().unwrap();
+    let dir = quake_dir.path().to_string_lossy().to_string();
+    // unset is fine, it's caught where it's needed
+    assert_eq!(validate_quake_dir(""), Ok(()));
+    assert_eq!(
+        validate_quake_dir(&dir),
+        Err(PathError::NoGameData(dir.clone()))
+    );
+    make_pak(quake_dir.path(), "id1");
+    assert_eq!(validate_quake_dir(&dir), Ok(()));
+
+    let missing = quake_dir.path().join("missing");
+    let missing = missing.to_string_lossy().to_string();
+    assert_eq!(
+        validate_dir(&missing),
+        Err(PathError::NotADirectory(missing.clone()))
+    );
+    assert_eq!(
+        validate_quake_exe(&missing),
+        Err(PathError::NotAFile(missing))
+    );
+    assert_eq!(validate_quake_exe(&dir), Err(PathError::NotAFile(dir)));
+}
+
+#[cfg(unix)]
+#[test]
+fn the_engine_has_to_be_executable() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = TempDir::new().unwrap();
+    let exe = dir.path().join("quakespasm");
+    fs::write(&exe, b"").unwrap();
+    fs::set_permissions(&exe, fs::Permissions::from_mode(0o644)).unwrap();
+    let exe_str = exe.to_string_lossy().to_string();
+    assert_eq!(
+        validate_quake_exe(&exe_str),
+        Err(PathError::NotExecutable(exe_str.clone()))
+    );
+    fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(validate_quake_exe(&exe_str), Ok(()));
+}
@@ -0,0 +1,26 @@
+use crate::download_progress::{DownloadProgress, ProgressReporter};
+use glib::Sender;
+use log::*;
+use std::sync::Mutex;
+
+/// Passes progress from an install thread over to the main loop.
+pub struct ChannelReporter {
+    // glib's sender can't be shared between threads on its own
+    sender: Mutex<Sender<DownloadProgress>>,
+}
+
+impl ChannelReporter {
+    pub fn new(sender: Sender<DownloadProgress>) -> Self {
+        Self {
+            sender: Mutex::new(sender),
+        }
+    }
+}
+
+impl ProgressReporter for ChannelReporter {
+    fn report(&self, progress: DownloadProgress) {
+        if let Err(e) = self.sender.lock().unwrap().send(progress) {
+            error!("Failed to send progress: {}", e);
+        }
+    }
+}
@@ -0,0 +1,1010 @@
+use crate::configuration::{
+    Configuration, EngineProfile, LocalMaps, Mirror, SeenMaps, DEFAULT_ENGINE_NAME,
+};
+use crate::database_refresh::{is_database_stale, refresh_database, DatabaseRefresh};
+use crate::dependencies::resolve_dependencies;
+use crate::download_error::DownloadError;
+use crate::download_progress::{DownloadProgress, ProgressReporter};
+use crate::game_player::{session_log_path, GameOutput, GamePlayerBuilder};
+use crate::http;
+use crate::install_detection::{detect_installs, SearchPaths};
+use crate::installer::{is_valid_map_id, verify_cached_zips, Installer, ZipStatus};
+use crate::integrity::verify_installation;
+use crate::launch_args::{split_args, LaunchArgsBuilder};
+use crate::quake_file::{initialize_data, QuakeFile};
+use crate::quake_paths::{validate_dir, validate_quake_dir, validate_quake_exe, MissionPack};
+use crate::whats_new::{find_changes, MapChange};
+use log::*;
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "Usage: rq_injector [command] [args]
+
+Running without a command starts the graphical interface.
+
+Commands:
+    list                       List every map in the database
+    search <text>              List maps whose id, title, author or date contain <text>
+    info <id>                  Show the details of a single map
+    install <id>               Install a map along with its requirements
+    install-custom <file or url> <id> [title]
+                               Install a map that isn't in the database into the <id> dir
+    uninstall <id> [--purge]   Remove an installed map. Files changed since install are
+                               kept unless --purge is given
+    play <id> [start map] [--dry-run]
+                               Launch a map with the engine picked for it. The full command
+                               is printed first, --dry-run stops there. What the engine
+                               prints is also saved to a log in the logs dir
+    launch-options <id> [options]
+                               Show or set the engine options used for one map, on top of
+                               the launch_options setting. Quote them as a single argument
+    verify [id] [--repair]     Check installed files against what was extracted, all maps
+                               when no id is given. --repair extracts broken maps again
+    verify-cache               Check downloaded zips against the database checksums
+    refresh                    Fetch the database if it changed on the server and list the
+                               maps that were added, updated or removed
+    whats-new [--mark-seen]    List maps added or changed since they were last marked as seen
+    config                     Show the current configuration
+    config <key> <value>       Change a setting (quake_dir, quake_exe, download_dir, offline,
+                               launch_options, database_max_age, proxy, proxy_user, proxy_password,
+                               connect_timeout, read_timeout, retries)
+    mirror                     List the mirrors, tried in order until one answers
+    mirror add <name> <base url>
+                               Add a mirror laid out like quaddicted.com
+    mirror add <name> <database url> <map url> <screenshot url>
+                               Add a mirror with its own urls, {id} stands for the map id
+    mirror remove <name>       Remove a mirror
+    engine                     List the engines. The one named default is quake_exe
+    engine add <name> <exe>    Add an engine profile
+    engine set <name> <key> <value>
+                               Change an engine (quake_exe, quake_dir, launch_options,
+                               game_flag, uses_basedir). An empty quake_dir uses the main one
+    engine remove <name>       Remove an engine, maps using it go back to the default one
+    engine use <id> <name>     Play an installed map with this engine from now on
+    detect [number]            List the Quake installs found in Steam, GOG and Heroic
+                               libraries and the usual system dirs, paired with any engines
+                               found there or on PATH. With a number, use that one's dir and
+                               engine for quake_dir and quake_exe
+    help                       Show this message
+
+Output is tab separated, one record per line. Map listings are:
+    installed  id  title  author  date  rating";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return usage(),
+    };
+    info!("Running command line: {} {:?}", command, rest);
+    http::configure(&Configuration::new().network_settings());
+    match command {
+        "list" => list(),
+        "search" => search(rest),
+        "info" => map_info(rest),
+        "install" => install(rest),
+        "install-custom" => install_custom(rest),
+        "uninstall" => uninstall(rest),
+        "play" => play(rest),
+        "launch-options" => launch_options(rest),
+        "verify" => verify(rest),
+        "verify-cache" => verify_cache(),
+        "refresh" => refresh(),
+        "whats-new" => whats_new(rest),
+        "config" => config(rest),
+        "mirror" => mirror(rest),
+        "engine" => engine(rest),
+        "detect" => detect(rest),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            EXIT_OK
+        }
+        _ => {
+            eprintln!("Unknown command: {}", command);
+            usage()
+        }
+    }
+}
+
+fn usage() -> i32 {
+    eprintln!("{}", USAGE);
+    EXIT_USAGE
+}
+
+fn fail<S: AsRef<str>>(msg: S) -> i32 {
+    error!("{}", msg.as_ref());
+    eprintln!("error: {}", msg.as_ref());
+    EXIT_FAILURE
+}
+
+fn list() -> i32 {
+    let config = Configuration::new();
+    let local_maps = LocalMaps::new(config.config_dir().clone());
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    files
+        .iter()
+        .for_each(|file| print_map_row(file, &local_maps));
+    EXIT_OK
+}
+
+fn search(args: &[String]) -> i32 {
+    if args.is_empty() {
+        return usage();
+    }
+    let text = args.join(" ").to_ascii_lowercase();
+    let config = Configuration::new();
+    let local_maps = LocalMaps::new(config.config_dir().clone());
+    let matches = |s: &String| s.to_ascii_lowercase().contains(&text);
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let mut found = false;
+    files
+        .iter()
+        .filter(|f| {
+            matches(f.id()) || matches(f.title()) || matches(f.author()) || matches(f.date())
+        })
+        .for_each(|file| {
+            found = true;
+            print_map_row(file, &local_maps);
+        });
+    match found {
+        true => EXIT_OK,
+        false => EXIT_FAILURE,
+    }
+}
+
+fn map_info(args: &[String]) -> i32 {
+    let map_id = match args.first() {
+        Some(id) => id,
+        None => return usage(),
+    };
+    let config = Configuration::new();
+    let local_maps = LocalMaps::new(config.config_dir().clone());
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let file = match files.iter().find(|f| f.id() == map_id) {
+        Some(f) => f,
+        None => return fail(format!("No map with id '{}'", map_id)),
+    };
+    let tech_info = file.tech_info();
+    let requirements = match tech_info.requirements() {
+        Some(reqs) => reqs
+            .req_file()
+            .iter()
+            .map(|r| r.id().to_owned())
+            .collect::<Vec<String>>()
+            .join(","),
+        None => String::new(),
+    };
+    let installed = local_maps.is_map_installed(map_id).to_string();
+    print_row(&["id", file.id()]);
+    print_row(&["title", file.title()]);
+    print_row(&["author", file.author()]);
+    print_row(&["date", file.date()]);
+    print_row(&["rating", file.rating()]);
+    print_row(&["size", file.size()]);
+    print_row(&["md5", file.md5()]);
+    print_row(&["installed", &installed]);
+    print_row(&[
+        "zipbasedir",
+        &tech_info.zip_base_dir().clone().unwrap_or_default(),
+    ]);
+    print_row(&[
+        "commandline",
+        &tech_info.command_line().clone().unwrap_or_default(),
+    ]);
+    print_row(&["startmap", &tech_info.start_map().join(",")]);
+    print_row(&["requirements", &requirements]);
+    print_row(&["description", file.description()]);
+    EXIT_OK
+}
+
+fn install(args: &[String]) -> i32 {
+    let map_id = match args.first() {
+        Some(id) => id.to_owned(),
+        None => return usage(),
+    };
+    let config = Configuration::new();
+    if let Some(msg) = check_install_config(&config) {
+        return fail(msg);
+    }
+    let mut local_maps = LocalMaps::new(config.config_dir().clone());
+    if local_maps.is_map_installed(&map_id) {
+        println!("{}", format_row(&["installed", &map_id, "existing"]));
+        return EXIT_OK;
+    }
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let (md5, zip_base_dir) = match files.iter().find(|f| f.id() == &map_id) {
+        Some(file) => (
+            file.md5().to_owned(),
+            file.tech_info().zip_base_dir().clone(),
+        ),
+        None => return fail(format!("No map with id '{}'", map_id)),
+    };
+    let dependencies = match resolve_dependencies(&map_id, &files, &local_maps) {
+        Ok(deps) => deps,
+        Err(e) => return fail(format!("Can't install {}: {}", map_id, e)),
+    };
+    // nobody to ask here, so it's only a warning
+    let mut missing_packs: Vec<MissionPack> = vec![];
+    let needed = files
+        .iter()
+        .filter(|f| f.id() == &map_id)
+        .chain(dependencies.iter());
+    for pack in needed.flat_map(|file| config.missing_mission_packs(file)) {
+        if !missing_packs.contains(&pack) {
+            missing_packs.push(pack);
+        }
+    }
+    for pack in missing_packs {
+        eprintln!(
+            "Warning: {} needs {}, which is not in quake_dir",
+            map_id, pack
+        );
+    }
+
+    let download_dir = config.download_dir().to_owned();
+    let quake_dir = config.quake_dir().to_owned();
+    let mirrors = config.mirrors().clone();
+    let (installer, install_result) = run_with_progress(move |reporter| {
+        let mut installer = Installer::new()
+            .with_download_dir(download_dir)
+            .with_quake_dir(quake_dir)
+            .with_map_id(map_id)
+            .with_md5(md5)
+            .with_zip_base_dir(zip_base_dir)
+            .with_dependencies(dependencies)
+            .with_mirrors(mirrors);
+        let result = installer.install_map(reporter);
+        (installer, result)
+    });
+    // dependencies that made it in before a failure are still on disk
+    for map_pack in installer.installed_dependencies() {
+        println!(
+            "{}",
+            format_row(&["installed", map_pack.id(), "dependency"])
+        );
+        local_maps.add_map(map_pack.clone());
+    }
+    if let Some(map_pack) = installer.installed_map_pack() {
+        println!("{}", format_row(&["installed", map_pack.id(), "requested"]));
+        local_maps.add_map(map_pack.clone());
+    }
+    local_maps.write_to_file(config.config_dir().clone());
+    match install_result {
+        Ok(_) => EXIT_OK,
+        Err(e) => fail(format!("Install failed: {}", e)),
+    }
+}
+
+fn install_custom(args: &[String]) -> i32 {
+    let (source, map_id) = match (args.first(), args.get(1)) {
+        (Some(source), Some(id)) => (source.to_owned(), id.to_owned()),
+        _ => return usage(),
+    };
+    let title = args[2..].join(" ");
+    if !is_valid_map_id(&map_id) {
+        return fail(format!(
+            "'{}' can't be used as a folder name. Use letters, numbers, '-' and '_'",
+            map_id
+        ));
+    }
+    let config = Configuration::new();
+    if let Some(msg) = check_install_config(&config) {
+        return fail(msg);
+    }
+    let mut local_maps = LocalMaps::new(config.config_dir().clone());
+    // without a database there's nothing to clash with but the installed maps
+    let is_taken = local_maps.is_map_installed(&map_id)
+        || load_files(&config)
+            .unwrap_or_default()
+            .iter()
+            .any(|f| f.id().eq_ignore_ascii_case(&map_id));
+    if is_taken {
+        return fail(format!("A map with id '{}' already exists", map_id));
+    }
+    let download_dir = config.download_dir().to_owned();
+    let quake_dir = config.quake_dir().to_owned();
+    let (installer, install_result) = run_with_progress(move |reporter| {
+        let mut installer = Installer::new()
+            .with_download_dir(download_dir)
+            .with_quake_dir(quake_dir)
+            .with_map_id(map_id);
+        let result = installer.install_custom(&source, &title, reporter);
+        (installer, result)
+    });
+    if let Some(map_pack) = installer.installed_map_pack() {
+        println!("{}", format_row(&["installed", map_pack.id(), "custom"]));
+        local_maps.add_map(map_pack.clone());
+        local_maps.write_to_file(config.config_dir().clone());
+    }
+    match install_result {
+        Ok(_) => EXIT_OK,
+        Err(e) => fail(format!("Install failed: {}", e)),
+    }
+}
+
+/// Runs an install job, printing its progress to stderr as it goes.
+fn run_with_progress<F>(job: F) -> (Installer, Result<(), DownloadError>)
+where
+    F: FnOnce(&dyn ProgressReporter) -> (Installer, Result<(), DownloadError>),
+{
+    let reporter = |progress: DownloadProgress| match progress.error() {
+        Some(e) => eprintln!("{}\tfailed\t{}", progress.file_name(), e),
+        None => eprintln!(
+            "{}\t{:.0}%\tresumed {} bytes",
+            progress.file_name(),
+            progress.percent().min(1.0) * 100.0,
+            progress.resumed_bytes()
+        ),
+    };
+    job(&reporter)
+}
+
+fn uninstall(args: &[String]) -> i32 {
+    let map_id = match args.first() {
+        Some(id) => id.to_owned(),
+        None => return usage(),
+    };
+    let purge = match args.get(1).map(|arg| arg.as_str()) {
+        Some("--purge") => true,
+        Some(_) => return usage(),
+        None => false,
+    };
+    let config = Configuration::new();
+    if let Some(msg) = check_install_config(&config) {
+        return fail(msg);
+    }
+    let mut local_maps = LocalMaps::new(config.config_dir().clone());
+    let map_pack = match local_maps.get_map(&map_id) {
+        Some(map_pack) => map_pack.clone(),
+        None => return fail(format!("'{}' is not installed", map_id)),
+    };
+    let installer = Installer::new()
+        .with_download_dir(config.download_dir().to_owned())
+        .with_quake_dir(config.quake_dir().to_owned())
+        .with_map_id(map_id.clone())
+        .with_installed_map_pack(Some(map_pack));
+    let kept_files = installer.uninstall_map(purge);
+    local_maps.remove_map(&map_id);
+    local_maps.write_to_file(config.config_dir().clone());
+    for file in kept_files {
+        println!("{}", format_row(&["kept", &file]));
+    }
+    println!("{}", format_row(&["uninstalled", &map_id]));
+    EXIT_OK
+}
+
+fn play(args: &[String]) -> i32 {
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    let mut args = args.iter().filter(|arg| *arg != "--dry-run");
+    let map_id = match args.next() {
+        Some(id) => id.to_owned(),
+        None => return usage(),
+    };
+    let config = Configuration::new();
+    let local_maps = LocalMaps::new(config.config_dir().clone());
+    let map_pack = match local_maps.get_map(&map_id) {
+        Some(map_pack) => map_pack,
+        None => return fail(format!("'{}' is not installed", map_id)),
+    };
+    let engine = config.get_engine(map_pack.engine());
+    if engine.quake_exe().is_empty() {
+        return fail(format!(
+            "The {} engine has no quake_exe, use 'config quake_exe <path>' or 'engine set'",
+            engine.name()
+        ));
+    }
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let file = files.iter().find(|f| f.id() == &map_id);
+    let database_line = file.and_then(|f| f.tech_info().command_line().to_owned());
+    let start_map = match args.next() {
+        Some(start_map) => Some(start_map.to_owned()),
+        None => file.and_then(|f| f.tech_info().start_map().first().cloned()),
+    };
+    let launch_args = LaunchArgsBuilder::default()
+        .engine(engine.clone())
+        .map_id(map_id.clone())
+        .start_map(start_map)
+        .database_line(database_line)
+        .user_options(config.launch_options().to_owned())
+        .map_options(map_pack.launch_options().to_owned())
+        .build()
+        .unwrap();
+    let argv = match launch_args.to_argv() {
+        Ok(argv) => argv,
+        Err(e) => return fail(format!("Couldn't read the launch options: {}", e)),
+    };
+    let game_player = GamePlayerBuilder::default()
+        .quake_exe(engine.quake_exe().to_owned())
+        .working_dir(engine.quake_dir().to_owned())
+        .map_id(map_id)
+        .args(argv)
+        .build()
+        .unwrap();
+    eprintln!("{}", game_player.command_line());
+    if dry_run {
+        return EXIT_OK;
+    }
+    let log_path = session_log_path(config.logs_dir(), game_player.map_id());
+    let game = game_player.launch(Some(&log_path), |output| match output {
+        GameOutput::Stdout(line) => println!("{}", line),
+        GameOutput::Stderr(line) => eprintln!("{}", line),
+        GameOutput::Exited(_) => (),
+    });
+    let code = match game {
+        Ok(game) => game.wait(),
+        Err(e) => return fail(format!("Couldn't start {}: {}", engine.quake_exe(), e)),
+    };
+    eprintln!("Log saved to {}", log_path.display());
+    match code {
+        Some(code) => code,
+        None => fail("The game didn't exit on its own"),
+    }
+}
+
+fn launch_options(args: &[String]) -> i32 {
+    let (map_id, options) = match args {
+        [map_id] => (map_id, None),
+        [map_id, options] => (map_id, Some(options)),
+        _ => return usage(),
+    };
+    let config = Configuration::new();
+    let mut local_maps = LocalMaps::new(config.config_dir().clone());
+    let current = match local_maps.get_map(map_id) {
+        Some(map_pack) => map_pack.launch_options().to_owned(),
+        None => return fail(format!("'{}' is not installed", map_id)),
+    };
+    match options {
+        None => {
+            println!("{}", current);
+            EXIT_OK
+        }
+        Some(options) => {
+            if let Err(e) = split_args(options) {
+                return fail(e.to_string());
+            }
+            local_maps.set_launch_options(map_id, options.trim().to_owned());
+            local_maps.write_to_file(config.config_dir().clone());
+            EXIT_OK
+        }
+    }
+}
+
+fn verify(args: &[String]) -> i32 {
+    let repair = args.iter().any(|arg| arg == "--repair");
+    let map_id = args.iter().find(|arg| !arg.starts_with("--"));
+    let config = Configuration::new();
+    if let Some(msg) = check_install_config(&config) {
+        return fail(msg);
+    }
+    let mut local_maps = LocalMaps::new(config.config_dir().clone());
+    let map_packs = match map_id {
+        Some(id) => match local_maps.get_map(id) {
+            Some(map_pack) => vec![map_pack.clone()],
+            None => return fail(format!("'{}' is not installed", id)),
+        },
+        None => local_maps.maps().clone(),
+    };
+    let mut broken = vec![];
+    for map_pack in map_packs {
+        let report = verify_installation(config.quake_dir(), &map_pack);
+        let id = report.map_id();
+        if report.is_clean() {
+            print_row(&[id, "ok"]);
+        }
+        if *report.is_untracked() {
+            print_row(&[id, "untracked"]);
+        }
+        report
+            .modified()
+            .iter()
+            .for_each(|file| print_row(&[id, "modified", file]));
+        report
+            .missing()
+            .iter()
+            .for_each(|file| print_row(&[id, "missing", file]));
+        report
+            .extra()
+            .iter()
+            .for_each(|file| print_row(&[id, "extra", file]));
+        if report.needs_repair() {
+            broken.push(map_pack);
+        }
+    }
+    if !repair {
+        return match broken.is_empty() {
+            true => EXIT_OK,
+            false => EXIT_FAILURE,
+        };
+    }
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let mut all_repaired = true;
+    for map_pack in broken {
+        let map_id = map_pack.id().to_owned();
+        let (md5, zip_base_dir) = match files.iter().find(|f| f.id() == &map_id) {
+            Some(file) => (
+                file.md5().to_owned(),
+                file.tech_info().zip_base_dir().clone(),
+            ),
+            None => (String::new(), None),
+        };
+        let download_dir = config.download_dir().to_owned();
+        let quake_dir = config.quake_dir().to_owned();
+        let mirrors = config.mirrors().clone();
+        let (installer, repair_result) = run_with_progress(move |reporter| {
+            let mut installer = Installer::new()
+                .with_download_dir(download_dir)
+                .with_quake_dir(quake_dir)
+                .with_map_id(map_id)
+                .with_md5(md5)
+                .with_zip_base_dir(zip_base_dir)
+                .with_installed_map_pack(Some(map_pack))
+                .with_mirrors(mirrors);
+            let result = installer.repair_map(reporter);
+            (installer, result)
+        });
+        match (repair_result, installer.installed_map_pack()) {
+            (Ok(_), Some(map_pack)) => {
+                print_row(&[installer.map_id(), "repaired"]);
+                local_maps.replace_map(map_pack.clone());
+            }
+            (Err(e), _) => {
+                all_repaired = false;
+                print_row(&[installer.map_id(), "failed", &e.to_string()]);
+            }
+            (Ok(_), None) => all_repaired = false,
+        }
+    }
+    local_maps.write_to_file(config.config_dir().clone());
+    match all_repaired {
+        true => EXIT_OK,
+        false => EXIT_FAILURE,
+    }
+}
+
+fn verify_cache() -> i32 {
+    let config = Configuration::new();
+    if config.download_dir().is_empty() {
+        return fail("download_dir is not set, use 'config download_dir <path>'");
+    }
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let mut all_valid = true;
+    for (map_id, status) in verify_cached_zips(config.download_dir(), &files) {
+        let (status, detail) = match status {
+            ZipStatus::Valid => ("valid", String::new()),
+            ZipStatus::Stale => ("stale", String::new()),
+            ZipStatus::Corrupt(e) => ("corrupt", e),
+            ZipStatus::Unknown => ("unknown", String::new()),
+        };
+        all_valid &= status == "valid" || status == "unknown";
+        print_row(&[status, &map_id, &detail]);
+    }
+    match all_valid {
+        true => EXIT_OK,
+        false => EXIT_FAILURE,
+    }
+}
+
+fn refresh() -> i32 {
+    let config = Configuration::new();
+    if *config.is_offline() {
+        return fail("Offline mode is on, use 'config offline false'");
+    }
+    let diff = match refresh_database(config.config_dir(), config.mirrors()) {
+        Ok(DatabaseRefresh::Updated(diff)) => diff,
+        Ok(DatabaseRefresh::Unchanged) => {
+            print_row(&["unchanged"]);
+            return EXIT_OK;
+        }
+        Err(e) => return fail(format!("Refresh failed: {}", e)),
+    };
+    let groups = [
+        ("added", diff.added()),
+        ("updated", diff.updated()),
+        ("removed", diff.removed()),
+    ];
+    for (label, files) in groups.iter() {
+        files
+            .iter()
+            .for_each(|file| print_row(&[*label, file.id(), file.title()]));
+    }
+    EXIT_OK
+}
+
+fn whats_new(args: &[String]) -> i32 {
+    let mark_seen = match args.first().map(|arg| arg.as_str()) {
+        Some("--mark-seen") => true,
+        Some(_) => return usage(),
+        None => false,
+    };
+    let config = Configuration::new();
+    let files = match load_files(&config) {
+        Ok(files) => files,
+        Err(e) => return fail(e),
+    };
+    let mut seen_maps = SeenMaps::new(config.config_dir().clone());
+    if !seen_maps.maps().is_empty() {
+        let changes = find_changes(&files, &seen_maps);
+        for file in files.iter().filter(|file| changes.contains_key(file.id())) {
+            let change = match changes[file.id()] {
+                MapChange::Added => "added",
+                MapChange::Updated => "updated",
+                MapChange::Rerated => "rerated",
+            };
+            print_row(&[change, file.id(), file.title()]);
+        }
+    }
+    // same as the window, a fresh config starts out having seen everything
+    if mark_seen || seen_maps.maps().is_empty() {
+        seen_maps.mark_seen(&files);
+        seen_maps.write_to_file(config.config_dir().clone());
+    }
+    EXIT_OK
+}
+
+fn config(args: &[String]) -> i32 {
+    let mut config = Configuration::new();
+    match args {
+        [] => {
+            print_row(&["quake_dir", config.quake_dir()]);
+            print_row(&["quake_exe", config.quake_exe()]);
+            print_row(&["download_dir", config.download_dir()]);
+            // found in quake_dir, can't be set
+            print_row(&["hipnotic", &config.hip_installed().to_string()]);
+            print_row(&["rogue", &config.rogue_installed().to_string()]);
+            print_row(&["offline", &config.is_offline().to_string()]);
+            print_row(&["launch_options", config.launch_options()]);
+            print_row(&[
+                "database_max_age",
+                &config.database_max_age_hours().to_string(),
+            ]);
+            print_row(&["proxy", config.proxy()]);
+            print_row(&["proxy_user", config.proxy_username()]);
+            print_row(&[
+                "connect_timeout",
+                &config.connect_timeout_secs().to_string(),
+            ]);
+            print_row(&["read_timeout", &config.read_timeout_secs().to_string()]);
+            print_row(&["retries", &config.retries().to_string()]);
+            EXIT_OK
+        }
+        [key, value] => {
+            match key.as_str() {
+                "quake_dir" => match validate_quake_dir(value) {
+                    Ok(_) => {
+                        config.set_quake_dir(value.to_owned());
+                        config.detect_mission_packs();
+                    }
+                    Err(e) => return fail(e.to_string()),
+                },
+                "quake_exe" => match validate_quake_exe(value) {
+                    Ok(_) => {
+                        config.set_quake_exe(value.to_owned());
+                    }
+                    Err(e) => return fail(e.to_string()),
+                },
+                "download_dir" => match validate_dir(value) {
+                    Ok(_) => {
+                        config.set_download_dir(value.to_owned());
+                    }
+                    Err(e) => return fail(e.to_string()),
+                },
+                "launch_options" => match split_args(value) {
+                    Ok(_) => {
+                        config.set_launch_options(value.trim().to_owned());
+                    }
+                    Err(e) => return fail(e.to_string()),
+                },
+                "offline" => match value.parse::<bool>() {
+                    Ok(b) => {
+                        config.set_is_offline(b);
+                    }
+                    Err(_) => return fail("offline must be 'true' or 'false'"),
+                },
+                "database_max_age" => match value.parse::<u32>() {
+                    Ok(hours) => {
+                        config.set_database_max_age_hours(hours);
+                    }
+                    Err(_) => return fail("database_max_age must be a number of hours"),
+                },
+                "proxy" => {
+                    config.set_proxy(value.to_owned());
+                }
+                "proxy_user" => {
+                    config.set_proxy_username(value.to_owned());
+                }
+                "proxy_password" => {
+                    config.set_proxy_password(value.to_owned());
+                }
+                "connect_timeout" => match value.parse::<u32>() {
+                    Ok(secs) => {
+                        config.set_connect_timeout_secs(secs);
+                    }
+                    Err(_) => return fail("connect_timeout must be a number of seconds"),
+                },
+                "read_timeout" => match value.parse::<u32>() {
+                    Ok(secs) => {
+                        config.set_read_timeout_secs(secs);
+                    }
+                    Err(_) => return fail("read_timeout must be a number of seconds"),
+                },
+                "retries" => match value.parse::<u32>() {
+                    Ok(retries) => {
+                        config.set_retries(retries);
+                    }
+                    Err(_) => return fail("retries must be a number"),
+                },
+                _ => return fail(format!("Unknown setting '{}'", key)),
+            };
+            config.write_to_file();
+            EXIT_OK
+        }
+        _ => usage(),
+    }
+}
+
+fn mirror(args: &[String]) -> i32 {
+    let mut config = Configuration::new();
+    let mut mirrors = config.mirrors().clone();
+    let new_mirror = match args {
+        [] => {
+            for mirror in &mirrors {
+                print_row(&[
+                    mirror.name().as_str(),
+                    mirror.database_url(),
+                    mirror.map_url(),
+                    mirror.screenshot_url(),
+                ]);
+            }
+            return EXIT_OK;
+        }
+        [command, name] if command == "remove" => {
+            let count = mirrors.len();
+            mirrors.retain(|mirror| mirror.name() != name);
+            if mirrors.len() == count {
+                return fail(format!("No mirror named '{}'", name));
+            }
+            None
+        }
+        [command, name, base_url] if command == "add" => {
+            Some(Mirror::from_base_url(name, base_url))
+        }
+        [command, name, database_url, map_url, screenshot_url] if command == "add" => {
+            if !map_url.contains("{id}") || !screenshot_url.contains("{id}") {
+                return fail("The map and screenshot urls need an {id} in them");
+            }
+            Some(Mirror::new(name, database_url, map_url, screenshot_url))
+        }
+        _ => return usage(),
+    };
+    if let Some(new_mirror) = new_mirror {
+        if mirrors
+            .iter()
+            .any(|mirror| mirror.name() == new_mirror.name())
+        {
+            return fail(format!(
+                "There's already a mirror named '{}'",
+                new_mirror.name()
+            ));
+        }
+        mirrors.push(new_mirror);
+    }
+    config.set_mirrors(mirrors);
+    config.write_to_file();
+    EXIT_OK
+}
+
+fn engine(args: &[String]) -> i32 {
+    let mut config = Configuration::new();
+    let mut engines = config.engines().clone();
+    let position = |engines: &Vec<EngineProfile>, name: &String| {
+        engines.iter().position(|engine| engine.name() == name)
+    };
+    match args {
+        [] => {
+            for engine in config.engine_profiles() {
+                print_row(&[
+                    engine.name().as_str(),
+                    engine.quake_exe(),
+                    engine.quake_dir(),
+                    engine.game_flag(),
+                    &engine.uses_basedir().to_string(),
+                    engine.launch_options(),
+                ]);
+            }
+            return EXIT_OK;
+        }
+        [command, name, quake_exe] if command == "add" => {
+            if name == DEFAULT_ENGINE_NAME || position(&engines, name).is_some() {
+                return fail(format!("There's already an engine named '{}'", name));
+            }
+            engines.push(EngineProfile::new(name, quake_exe));
+        }
+        [command, name, key, value] if command == "set" => {
+            let index = match position(&engines, name) {
+                Some(index) => index,
+                None if name == DEFAULT_ENGINE_NAME => {
+                    return fail("The default engine is set with 'config quake_exe'")
+                }
+                None => return fail(format!("No engine named '{}'", name)),
+            };
+            let engine = &mut engines[index];
+            match key.as_str() {
+                "quake_exe" => {
+                    engine.set_quake_exe(value.to_owned());
+                }
+                "quake_dir" => {
+                    engine.set_quake_dir(value.to_owned());
+                }
+                "launch_options" => match split_args(value) {
+                    Ok(_) => {
+                        engine.set_launch_options(value.trim().to_owned());
+                    }
+                    Err(e) => return fail(e.to_string()),
+                },
+                "game_flag" => {
+                    engine.set_game_flag(value.to_owned());
+                }
+                "uses_basedir" => match value.parse::<bool>() {
+                    Ok(b) => {
+                        engine.set_uses_basedir(b);
+                    }
+                    Err(_) => return fail("uses_basedir must be 'true' or 'false'"),
+                },
+                _ => return fail(format!("Unknown engine setting '{}'", key)),
+            };
+        }
+        [command, name] if command == "remove" => match position(&engines, name) {
+            Some(index) => {
+                engines.remove(index);
+            }
+            None => return fail(format!("No engine named '{}'", name)),
+        },
+        [command, map_id, name] if command == "use" => {
+            if name != DEFAULT_ENGINE_NAME && position(&engines, name).is_none() {
+                return fail(format!("No engine named '{}'", name));
+            }
+            let mut local_maps = LocalMaps::new(config.config_dir().clone());
+            if !local_maps.is_map_installed(map_id) {
+                return fail(format!("'{}' is not installed", map_id));
+            }
+            local_maps.set_engine(map_id, name.to_owned());
+            local_maps.write_to_file(config.config_dir().clone());
+            return EXIT_OK;
+        }
+        _ => return usage(),
+    };
+    config.set_engines(engines);
+    config.write_to_file();
+    EXIT_OK
+}
+
+fn detect(args: &[String]) -> i32 {
+    let installs = detect_installs(&SearchPaths::from_env());
+    match args {
+        [] => {
+            for (number, install) in installs.iter().enumerate() {
+                let quake_exe = match install.quake_exe() {
+                    Some(exe) => exe.to_string_lossy().to_string(),
+                    None => String::new(),
+                };
+                print_row(&[
+                    &(number + 1).to_string(),
+                    &install.source().to_string(),
+                    &install.quake_dir().to_string_lossy(),
+                    &quake_exe,
+                ]);
+            }
+            EXIT_OK
+        }
+        [number] => {
+            let install = match number.parse::<usize>() {
+                Ok(number) if number > 0 && number <= installs.len() => &installs[number - 1],
+                _ => return fail(format!("No install numbered '{}'", number)),
+            };
+            let mut config = Configuration::new();
+            config.set_quake_dir(install.quake_dir().to_string_lossy().to_string());
+            config.detect_mission_packs();
+            if let Some(exe) = install.quake_exe() {
+                config.set_quake_exe(exe.to_string_lossy().to_string());
+            }
+            config.write_to_file();
+            EXIT_OK
+        }
+        _ => usage(),
+    }
+}
+
+fn check_install_config(config: &Configuration) -> Option<String> {
+    if config.quake_dir().is_empty() {
+        Some("quake_dir is not set, use 'config quake_dir <path>'".to_string())
+    } else if config.download_dir().is_empty() {
+        Some("download_dir is not set, use 'config download_dir <path>'".to_string())
+    } else if let Err(e) = validate_quake_dir(config.quake_dir()) {
+        Some(format!("quake_dir {}", e))
+    } else if let Err(e) = validate_dir(config.download_dir()) {
+        Some(format!("download_dir {}", e))
+    } else {
+        None
+    }
+}
+
+// a saved copy of the database is good enough to work with, it just gets a warning
+fn load_files(config: &Configuration) -> Result<Vec<QuakeFile>, String> {
+    // same as the gui does on start, an old database gets fetched again first
+    let max_age_hours = *config.database_max_age_hours();
+    if !*config.is_offline() && is_database_stale(config.config_dir(), max_age_hours) {
+        if let Err(e) = refresh_database(config.config_dir(), config.mirrors()) {
+            eprintln!("warning: {}, using the last saved copy", e);
+        }
+    }
+    let (files, error) = initialize_data(
+        config.config_dir().clone(),
+        *config.is_offline(),
+        config.mirrors(),
+    );
+    let mut files = files.files().clone();
+    match error {
+        Some(e) if files.is_empty() => return Err(e.to_string()),
+        Some(e) => eprintln!("warning: {}, using the last saved copy", e),
+        None => (),
+    }
+    let local_maps = LocalMaps::new(config.config_dir().clone());
+    let custom_files = local_maps
+        .maps()
+        .iter()
+        .filter(|map_pack| map_pack.is_custom())
+        .map(QuakeFile::custom)
+        .collect::<Vec<QuakeFile>>();
+    files.extend(custom_files);
+    Ok(files)
+}
+
+fn print_map_row(file: &QuakeFile, local_maps: &LocalMaps) {
+    let installed = match local_maps.is_map_installed(file.id()) {
+        true => "1",
+        false => "0",
+    };
+    print_row(&[
+        installed,
+        file.id(),
+        file.title(),
+        file.author(),
+        file.date(),
+        file.rating(),
+    ]);
+}
+
+fn print_row(fields: &[&str]) {
+    println!("{}", format_row(fields));
+}
+
+// tabs and newlines inside a field would break the record format
+fn format_row(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|f| f.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<String>>()
+        .join("\t")
+}
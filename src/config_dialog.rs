@@ -1,7 +1,9 @@
 use crate::configuration::Configuration;
 use crate::engine_config_tab::EngineConfigTab;
+use crate::engines_config_tab::EnginesConfigTab;
 use crate::initializable::Initializable;
 use crate::locales::Locale;
+use crate::network_config_tab::NetworkConfigTab;
 use crate::personal_config_tab::PersonalConfigTab;
 use gtk::prelude::*;
 use gtk::{Builder, Button, Dialog};
@@ -16,6 +18,8 @@ pub struct ConfigDialog {
     pub btn_cancel: Button,
     pub engine_config_tab: EngineConfigTab,
     pub personal_config_tab: PersonalConfigTab,
+    pub network_config_tab: NetworkConfigTab,
+    pub engines_config_tab: EnginesConfigTab,
 }
 
 impl ConfigDialog {
@@ -32,18 +36,24 @@ impl ConfigDialog {
             .expect("Failed to get btn_cancel");
         let engine_config_tab = EngineConfigTab::create_from_builder(builder);
         let personal_config_tab = PersonalConfigTab::create_from_builder(builder);
+        let network_config_tab = NetworkConfigTab::create_from_builder(builder);
+        let engines_config_tab = EnginesConfigTab::create_from_builder(builder);
         Self {
             dlg_config,
             btn_ok,
             btn_cancel,
             engine_config_tab,
             personal_config_tab,
+            network_config_tab,
+            engines_config_tab,
         }
     }
 
     pub fn show(&self, shared_config: Rc<RefCell<Configuration>>) {
         self.engine_config_tab.show(shared_config.clone());
-        self.personal_config_tab.show(shared_config);
+        self.personal_config_tab.show(shared_config.clone());
+        self.network_config_tab.show(shared_config.clone());
+        self.engines_config_tab.show(shared_config);
         self.dlg_config.show_all();
     }
 }
@@ -55,5 +65,7 @@ impl Initializable for ConfigDialog {
         self.btn_cancel.set_label(locale.universal_cancel_button());
         self.engine_config_tab.init_text(locale);
         self.personal_config_tab.init_text(locale);
+        self.network_config_tab.init_text(locale);
+        self.engines_config_tab.init_text(locale);
     }
 }
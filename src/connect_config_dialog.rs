@@ -1,9 +1,19 @@
+use crate::configuration::DEFAULT_ENGINE_NAME;
+use crate::connect_download_queue::start_pending;
 use crate::gui_data::GuiData;
+use crate::http;
 use crate::initializable::Initializable;
+use crate::install_detection::{detect_installs, DetectedInstall, SearchPaths};
 use crate::locales::Locale;
+use crate::quake_paths::{validate_dir, validate_quake_dir, validate_quake_exe, PathError};
+use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
 use gtk::prelude::*;
-use gtk::{Button, Dialog, Entry, FileChooserAction, ResponseType};
+use gtk::{
+    Button, ButtonsType, Dialog, DialogFlags, Entry, FileChooserAction, MessageDialog, MessageType,
+    ResponseType,
+};
 use log::*;
+use std::thread;
 
 pub fn connect_activate(gui_data: &GuiData) {
     trace!("Initializing config activation");
@@ -35,9 +45,57 @@ pub fn connect_ok(gui_data: &GuiData) {
     let filter_bar = gui_data.filter_bar.clone();
     let main_menu = gui_data.main_menu.clone();
     let list_view = gui_data.list_view.clone();
+    let shared_queue_state = gui_data.shared_queue_state.clone();
+    let gui_data = gui_data.clone();
     button.connect_clicked(move |_| {
         trace!("Config ok clicked");
-        let mut borrow = shared_config_state.borrow_mut();
+        let tab = &dialog.engine_config_tab;
+        // the warning's main loop runs receivers that read the config, so it can't stay borrowed
+        let invalid_text = {
+            let borrow = shared_config_state.borrow();
+            let locale = borrow.current_locale();
+            let problems = invalid_paths(
+                &[
+                    (
+                        locale.config_dialog_quake_dir_text(),
+                        validate_quake_dir(&tab.ent_quake_dir.get_buffer().get_text()),
+                    ),
+                    (
+                        locale.config_dialog_quake_exe_text(),
+                        validate_quake_exe(&tab.ent_quake_exe.get_buffer().get_text()),
+                    ),
+                    (
+                        locale.config_dialog_download_dir_text(),
+                        validate_dir(&tab.ent_download_dir.get_buffer().get_text()),
+                    ),
+                ],
+                locale,
+            );
+            match problems.is_empty() {
+                true => None,
+                false => {
+                    warn!("Not saving the configuration: {:?}", problems);
+                    Some(format!(
+                        "{}\n\n{}",
+                        locale.config_dialog_invalid_paths_text(),
+                        problems.join("\n")
+                    ))
+                }
+            }
+        };
+        if let Some(text) = invalid_text {
+            // left open so they can be fixed
+            let message = MessageDialog::new(
+                Some(&dialog.dlg_config),
+                DialogFlags::MODAL,
+                MessageType::Warning,
+                ButtonsType::Ok,
+                &text,
+            );
+            message.run();
+            message.close();
+            return;
+        }
         let quake_exe = dialog
             .engine_config_tab
             .ent_quake_exe
@@ -53,8 +111,19 @@ pub fn connect_ok(gui_data: &GuiData) {
             .ent_download_dir
             .get_buffer()
             .get_text();
-        let hip_checked = dialog.engine_config_tab.chk_hipnotic.get_active();
-        let rogue_checked = dialog.engine_config_tab.chk_rogue.get_active();
+        let launch_options = dialog
+            .engine_config_tab
+            .ent_command_opts
+            .get_buffer()
+            .get_text();
+        let max_downloads = dialog
+            .engine_config_tab
+            .spin_max_downloads
+            .get_value_as_int() as u8;
+        let database_max_age = dialog
+            .engine_config_tab
+            .spin_database_max_age
+            .get_value_as_int() as u32;
         let language = dialog
             .personal_config_tab
             .dropdown_choose_language
@@ -65,18 +134,44 @@ pub fn connect_ok(gui_data: &GuiData) {
             .dropdown_choose_dateformat
             .get_active_text()
             .unwrap();
+        let network_tab = &dialog.network_config_tab;
+        let proxy = network_tab.ent_proxy.get_buffer().get_text();
+        let proxy_user = network_tab.ent_proxy_user.get_buffer().get_text();
+        let proxy_password = network_tab.ent_proxy_password.get_buffer().get_text();
+        let connect_timeout = network_tab.spin_connect_timeout.get_value_as_int() as u32;
+        let read_timeout = network_tab.spin_read_timeout.get_value_as_int() as u32;
+        let retries = network_tab.spin_retries.get_value_as_int() as u32;
+        let engines = dialog.engines_config_tab.profiles.borrow().clone();
+        let mut borrow = shared_config_state.borrow_mut();
         borrow.set_quake_exe(quake_exe);
         borrow.set_quake_dir(quake_dir);
         borrow.set_download_dir(download_dir);
-        borrow.set_hip_installed(hip_checked);
-        borrow.set_rogue_installed(rogue_checked);
+        // a bad quote is pointed out when the map is launched
+        borrow.set_launch_options(launch_options.trim().to_string());
+        borrow.detect_mission_packs();
+        borrow.set_max_concurrent_downloads(max_downloads);
+        borrow.set_database_max_age_hours(database_max_age);
         borrow.set_date_format(date_format.to_string());
         borrow.set_language(language.to_string());
+        borrow.set_proxy(proxy.trim().to_string());
+        borrow.set_proxy_username(proxy_user);
+        borrow.set_proxy_password(proxy_password);
+        borrow.set_connect_timeout_secs(connect_timeout);
+        borrow.set_read_timeout_secs(read_timeout);
+        borrow.set_retries(retries);
+        borrow.set_engines(engines);
+        http::configure(&borrow.network_settings());
         detail_pane.init_text(borrow.current_locale());
         filter_bar.init_text(borrow.current_locale());
         main_menu.init_text(borrow.current_locale());
         list_view.init_text(borrow.current_locale());
         dialog.dlg_config.clone().hide();
+        // starting jobs needs the config again
+        drop(borrow);
+        shared_queue_state
+            .borrow_mut()
+            .set_max_concurrent(max_downloads as usize);
+        start_pending(&gui_data);
     });
 }
 
@@ -120,6 +215,130 @@ pub fn connect_selects(gui_data: &GuiData) {
         FileChooserAction::Open,
         config.borrow().current_locale().clone(),
     );
+    connect_input(
+        dialog.engines_config_tab.btn_engine_exe.clone(),
+        dialog.engines_config_tab.ent_engine_exe.clone(),
+        dialog.dlg_config.clone(),
+        config
+            .borrow()
+            .current_locale()
+            .config_dialog_quake_exe_text()
+            .to_string(),
+        FileChooserAction::Open,
+        config.borrow().current_locale().clone(),
+    );
+}
+
+pub fn connect_engine_profiles(gui_data: &GuiData) {
+    trace!("Initializing engine profiles");
+    let tab = gui_data.config_dialog.engines_config_tab.clone();
+
+    let con_tab = tab.clone();
+    tab.dropdown_engine_profiles
+        .connect_changed(move |dropdown| {
+            let name = match dropdown.get_active_id() {
+                Some(name) => name.to_string(),
+                None => return,
+            };
+            let profiles = con_tab.profiles.borrow();
+            con_tab.fill_fields(profiles.iter().find(|profile| profile.name() == &name));
+        });
+
+    let con_tab = tab.clone();
+    tab.ent_engine_name.connect_changed(move |entry| {
+        let name = entry.get_buffer().get_text();
+        let name = name.trim();
+        // the default engine is the one on the engine tab
+        con_tab
+            .btn_engine_save
+            .set_sensitive(!name.is_empty() && name != DEFAULT_ENGINE_NAME);
+    });
+
+    let con_tab = tab.clone();
+    tab.btn_engine_save.connect_clicked(move |_| {
+        let profile = con_tab.profile_from_fields();
+        let name = profile.name().clone();
+        debug!("Saving engine profile {}", name);
+        {
+            let mut profiles = con_tab.profiles.borrow_mut();
+            match profiles.iter_mut().find(|old| old.name() == &name) {
+                Some(old) => *old = profile,
+                None => profiles.push(profile),
+            }
+        }
+        con_tab.refresh_list(Some(&name));
+    });
+
+    let con_tab = tab.clone();
+    tab.btn_engine_remove.connect_clicked(move |_| {
+        let name = match con_tab.dropdown_engine_profiles.get_active_id() {
+            Some(name) => name.to_string(),
+            None => return,
+        };
+        debug!("Removing engine profile {}", name);
+        con_tab
+            .profiles
+            .borrow_mut()
+            .retain(|profile| profile.name() != &name);
+        con_tab.refresh_list(None);
+    });
+}
+
+/// Searches the usual places for Quake and offers what it finds on the engine tab.
+pub fn connect_install_detection(gui_data: &GuiData) {
+    trace!("Initializing install detection");
+    let (sender, receiver): (Sender<Vec<DetectedInstall>>, Receiver<Vec<DetectedInstall>>) =
+        MainContext::channel(PRIORITY_DEFAULT);
+    let tab = gui_data.config_dialog.engine_config_tab.clone();
+    let shared_config_state = gui_data.shared_config_state.clone();
+    let rec_tab = tab.clone();
+    receiver.attach(None, move |installs| {
+        info!("Found {} Quake installs", installs.len());
+        rec_tab.set_detected(installs, shared_config_state.borrow().current_locale());
+        Continue(true)
+    });
+
+    tab.btn_detect_installs.connect_clicked(move |button| {
+        button.set_sensitive(false);
+        let sender = sender.clone();
+        thread::Builder::new()
+            .name("detect".to_string())
+            .spawn(move || {
+                let installs = detect_installs(&SearchPaths::from_env());
+                sender.send(installs).expect("Couldn't send");
+            })
+            .expect("Failed to spawn detect thread");
+    });
+
+    let con_tab = tab.clone();
+    tab.btn_use_detected
+        .connect_clicked(move |_| con_tab.use_detected());
+}
+
+/// Keeps the mission pack checkboxes in step with the quake dir being typed in.
+pub fn connect_mission_packs(gui_data: &GuiData) {
+    let tab = gui_data.config_dialog.engine_config_tab.clone();
+    let con_tab = tab.clone();
+    tab.ent_quake_dir.connect_changed(move |entry| {
+        con_tab.show_mission_packs(&entry.get_buffer().get_text());
+    });
+}
+
+// one line for each field that's wrong, named the way the dialog names it
+fn invalid_paths(checks: &[(&String, Result<(), PathError>)], locale: &Locale) -> Vec<String> {
+    checks
+        .iter()
+        .filter_map(|(name, result)| {
+            let e = result.as_ref().err()?;
+            let (template, path) = match e {
+                PathError::NotADirectory(path) => (locale.path_not_a_directory_text(), path),
+                PathError::NoGameData(path) => (locale.path_no_game_data_text(), path),
+                PathError::NotAFile(path) => (locale.path_not_a_file_text(), path),
+                PathError::NotExecutable(path) => (locale.path_not_executable_text(), path),
+            };
+            Some(format!("{}: {}", name, template.replace("{}", path)))
+        })
+        .collect()
 }
 
 fn connect_input(
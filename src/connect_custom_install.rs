@@ -0,0 +1,280 @@
+use crate::channel_reporter::ChannelReporter;
+use crate::connect_detail_buttons::get_path_string_from_id;
+use crate::download_error::DownloadError;
+use crate::download_progress::DownloadProgress;
+use crate::download_queue::QueueState;
+use crate::gui_data::GuiData;
+use crate::installer::{is_valid_map_id, Installer};
+use crate::quake_file::QuakeFile;
+use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
+use gtk::prelude::*;
+use gtk::{FileChooserAction, ResponseType};
+use log::*;
+use std::cell::Cell;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+type InstallResult = (Installer, Result<(), DownloadError>);
+
+pub fn connect_install_from_file(gui_data: &GuiData) {
+    trace!("Initializing install from file");
+    let menu_install_file = gui_data.main_menu.menu_install_file.clone();
+    let gui_data = gui_data.clone();
+    menu_install_file.connect_activate(move |_| {
+        info!("Install from file request made");
+        let locale = gui_data
+            .shared_config_state
+            .borrow()
+            .current_locale()
+            .clone();
+        let title = locale
+            .file_chooser_title()
+            .replace("{}", locale.custom_dialog_file_chooser_text());
+        let file_dialog = gtk::FileChooserDialog::with_buttons(
+            Some(&title),
+            Some(&gui_data.window),
+            FileChooserAction::Open,
+            &[
+                (locale.universal_cancel_button(), ResponseType::Cancel),
+                (locale.universal_ok_button(), ResponseType::Accept),
+            ],
+        );
+        let response = file_dialog.run();
+        let file_name = file_dialog.get_filename();
+        file_dialog.hide();
+        if let (ResponseType::Accept, Some(path)) = (response, file_name) {
+            let source = path.to_string_lossy().to_string();
+            debug!("Picked {} to install", source);
+            gui_data
+                .custom_install_dialog
+                .show(&source, &default_map_id(&source));
+        }
+    });
+}
+
+pub fn connect_install_from_url(gui_data: &GuiData) {
+    trace!("Initializing install from url");
+    let menu_install_url = gui_data.main_menu.menu_install_url.clone();
+    let dialog = gui_data.custom_install_dialog.clone();
+    menu_install_url.connect_activate(move |_| {
+        info!("Install from url request made");
+        dialog.show("", "");
+    });
+}
+
+pub fn connect_custom_install_ok(gui_data: &GuiData) {
+    trace!("Initializing custom install ok button");
+    let btn_ok = gui_data.custom_install_dialog.btn_custom_install_ok.clone();
+    let gui_data = gui_data.clone();
+    btn_ok.connect_clicked(move |_| {
+        let dialog = &gui_data.custom_install_dialog;
+        let source = dialog.ent_custom_source.get_text().trim().to_string();
+        if source.is_empty() {
+            return;
+        }
+        let map_id = match dialog.ent_custom_id.get_text().trim() {
+            "" => default_map_id(&source),
+            id => id.to_string(),
+        };
+        let title = dialog.ent_custom_title.get_text().trim().to_string();
+        info!("Custom install ok pressed for {} from {}", map_id, source);
+        {
+            let config = gui_data.shared_config_state.borrow();
+            let locale = config.current_locale();
+            if !is_valid_map_id(&map_id) {
+                gui_data
+                    .output_dialog
+                    .show_text(locale.custom_invalid_id_text(), locale);
+                return;
+            }
+            if is_map_id_taken(&gui_data, &map_id) {
+                gui_data
+                    .output_dialog
+                    .show_text(locale.custom_id_taken_text(), locale);
+                return;
+            }
+        }
+        dialog.dlg_custom_install.hide();
+        begin_custom_install(&gui_data, source, map_id, title);
+    });
+}
+
+pub fn connect_custom_install_cancel(gui_data: &GuiData) {
+    trace!("Initializing custom install cancel button");
+    let btn_cancel = gui_data
+        .custom_install_dialog
+        .btn_custom_install_cancel
+        .clone();
+    let dialog = gui_data.custom_install_dialog.dlg_custom_install.clone();
+    btn_cancel.connect_clicked(move |_| {
+        dialog.hide();
+    });
+}
+
+/// Custom maps only exist because they're installed, so uninstalling one drops it from the list.
+pub fn remove_custom_entry(gui_data: &GuiData, map_id: &String) {
+    info!("Removing custom map {} from the list", map_id);
+    gui_data
+        .shared_files_state
+        .borrow_mut()
+        .retain(|file| file.id() != map_id);
+    let path_string = get_path_string_from_id(gui_data, map_id);
+    let list_store = gui_data.list_view.list_store.clone();
+    if let Some(iter) = list_store.get_iter_from_string(&path_string) {
+        list_store.remove(&iter);
+    }
+}
+
+fn begin_custom_install(gui_data: &GuiData, source: String, map_id: String, title: String) {
+    let is_running = Rc::new(Cell::new(false));
+    let cancel_flag = Arc::new(AtomicBool::new(false));
+    let row = {
+        let config = gui_data.shared_config_state.borrow();
+        gui_data
+            .queue_panel
+            .add_row(&map_id, config.current_locale())
+    };
+
+    let cancel_gui_data = gui_data.clone();
+    let cancel_map_id = map_id.clone();
+    let cancel_is_running = is_running.clone();
+    let cancel_flag_clone = cancel_flag.clone();
+    row.btn_cancel.connect_clicked(move |_| {
+        match cancel_is_running.get() {
+            // the thread stops once it notices the flag
+            true => cancel_flag_clone.store(true, Ordering::Relaxed),
+            false => cancel_gui_data.queue_panel.remove_row(&cancel_map_id),
+        }
+    });
+
+    let retry_gui_data = gui_data.clone();
+    let retry_is_running = is_running.clone();
+    let retry_flag = cancel_flag.clone();
+    let (retry_source, retry_map_id, retry_title) = (source.clone(), map_id.clone(), title.clone());
+    row.btn_retry.connect_clicked(move |_| {
+        trace!("Custom install retry clicked for {}", retry_map_id);
+        retry_flag.store(false, Ordering::Relaxed);
+        run_custom_install(
+            &retry_gui_data,
+            retry_source.clone(),
+            retry_map_id.clone(),
+            retry_title.clone(),
+            retry_is_running.clone(),
+            retry_flag.clone(),
+        );
+    });
+
+    run_custom_install(gui_data, source, map_id, title, is_running, cancel_flag);
+}
+
+fn run_custom_install(
+    gui_data: &GuiData,
+    source: String,
+    map_id: String,
+    title: String,
+    is_running: Rc<Cell<bool>>,
+    cancel_flag: Arc<AtomicBool>,
+) {
+    is_running.set(true);
+    {
+        let config = gui_data.shared_config_state.borrow();
+        gui_data
+            .queue_panel
+            .update_state(&map_id, &QueueState::Running, config.current_locale());
+    }
+    let (sender, receiver): (Sender<InstallResult>, Receiver<InstallResult>) =
+        MainContext::channel(PRIORITY_DEFAULT);
+    let (progress_sender, progress_receiver): (
+        Sender<DownloadProgress>,
+        Receiver<DownloadProgress>,
+    ) = MainContext::channel(PRIORITY_DEFAULT);
+
+    let queue_panel = gui_data.queue_panel.clone();
+    let progress_map_id = map_id.clone();
+    progress_receiver.attach(None, move |dl_progress| {
+        if dl_progress.error().is_none() {
+            let file_name = dl_progress.file_name();
+            queue_panel.update_progress(&progress_map_id, file_name, *dl_progress.percent());
+        }
+        Continue(true)
+    });
+
+    let rec_gui_data = gui_data.clone();
+    receiver.attach(None, move |(installer, result)| {
+        is_running.set(false);
+        let map_id = installer.map_id();
+        let state = match result {
+            Ok(()) => QueueState::Done,
+            Err(DownloadError::Cancelled) => QueueState::Cancelled,
+            Err(e) => QueueState::Failed(e.to_string()),
+        };
+        {
+            let config = rec_gui_data.shared_config_state.borrow();
+            rec_gui_data
+                .queue_panel
+                .update_state(map_id, &state, config.current_locale());
+        }
+        if let Some(map_pack) = installer.installed_map_pack().clone() {
+            let file = QuakeFile::custom(&map_pack);
+            rec_gui_data
+                .shared_install_state
+                .borrow_mut()
+                .add_map(map_pack);
+            rec_gui_data.list_view.append_file(&rec_gui_data, &file);
+            rec_gui_data.shared_files_state.borrow_mut().push(file);
+            rec_gui_data.queue_panel.remove_row(map_id);
+        }
+        Continue(false)
+    });
+
+    let download_dir = gui_data.shared_config_state.borrow().download_dir().clone();
+    let quake_dir = gui_data.shared_config_state.borrow().quake_dir().clone();
+    thread::Builder::new()
+        .name(format!("custom-{}", map_id))
+        .spawn(move || {
+            let mut installer = Installer::new()
+                .with_download_dir(download_dir)
+                .with_quake_dir(quake_dir)
+                .with_map_id(map_id)
+                .with_cancel_flag(cancel_flag);
+            let result =
+                installer.install_custom(&source, &title, &ChannelReporter::new(progress_sender));
+            sender.send((installer, result)).expect("Couldn't send");
+        })
+        .expect("Failed to spawn custom install thread");
+}
+
+fn is_map_id_taken(gui_data: &GuiData, map_id: &String) -> bool {
+    // quake dirs are case insensitive on windows
+    let is_listed = gui_data
+        .shared_files_state
+        .borrow()
+        .iter()
+        .any(|file| file.id().eq_ignore_ascii_case(map_id));
+    let is_installed = gui_data
+        .shared_install_state
+        .borrow()
+        .maps()
+        .iter()
+        .any(|map_pack| map_pack.id().eq_ignore_ascii_case(map_id));
+    is_listed || is_installed || gui_data.queue_panel.has_row(map_id)
+}
+
+// the archive's name minus its extension, with anything a folder name can't hold swapped out
+fn default_map_id(source: &str) -> String {
+    let path = source.split(['?', '#']).next().unwrap_or(source);
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    stem.to_ascii_lowercase()
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '-' {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
@@ -1,70 +1,69 @@
-use crate::download_progress::DownloadProgress;
+use crate::configuration::DEFAULT_ENGINE_NAME;
+use crate::connect_custom_install::remove_custom_entry;
+use crate::connect_download_queue::enqueue_install;
+use crate::connect_integrity::check_installs;
 use crate::game_player::*;
 use crate::gui_data::GuiData;
-use crate::initializable::Initializable;
 use crate::installer::Installer;
+use crate::launch_args::{join_args, LaunchArgsBuilder};
 use crate::quake_file::QuakeFile;
 use gio::prelude::*;
 use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
 use gtk::prelude::*;
 use log::*;
 use rand::prelude::*;
-use std::process::Output;
 use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
 use std::thread;
 
 static THREAD_COUNTER: AtomicU8 = AtomicU8::new(0);
 
 pub fn connect_install_map(gui_data: &GuiData) {
-    let (sender, receiver): (Sender<Installer>, Receiver<Installer>) =
-        MainContext::channel(PRIORITY_DEFAULT);
-    let (progress_sender, progress_receiver): (
-        Sender<DownloadProgress>,
-        Receiver<DownloadProgress>,
-    ) = MainContext::channel(PRIORITY_DEFAULT);
     let button = gui_data.detail_pane.btn_install.clone();
-    let detail_pane = gui_data.detail_pane.clone();
-    let rec_gui_data = gui_data.clone();
-    let shared_install_state = rec_gui_data.shared_install_state.clone();
-    receiver.attach(None, move |installer| {
-        release_thread_name();
-        set_installed_state(&rec_gui_data, true, &installer);
-        let map_pack = installer.installed_map_pack().clone().unwrap();
-        shared_install_state.borrow_mut().add_map(map_pack);
-        Continue(true)
-    });
-    progress_receiver.attach(None, move |dl_progress| {
-        detail_pane.update_progress_bar(&dl_progress.file_name(), *dl_progress.percent());
-        Continue(true)
-    });
     let con_gui_data = gui_data.clone();
 
     button.connect_clicked(move |_| {
         trace!("Install button clicked");
         let map_id = get_selected_map_id(&con_gui_data)
             .expect("Nothing was selected, so this shouldn't happen");
-        install_map(
-            &con_gui_data,
-            progress_sender.clone(),
-            sender.clone(),
-            map_id,
-        );
+        enqueue_install(&con_gui_data, map_id);
     });
 }
 
+// the installer and whatever files were kept because they changed since the install
+type UninstallResult = (Installer, Vec<String>);
+
 pub fn connect_uninstall_map(gui_data: &GuiData) {
-    let (sender, receiver): (Sender<Installer>, Receiver<Installer>) =
+    let (sender, receiver): (Sender<UninstallResult>, Receiver<UninstallResult>) =
         MainContext::channel(PRIORITY_DEFAULT);
     let button = gui_data.detail_pane.btn_uninstall.clone();
     let rec_gui_data = gui_data.clone();
     let shared_install_state = rec_gui_data.shared_install_state.clone();
     let shared_config_state = rec_gui_data.shared_config_state.clone();
     let rec_shared_install_state = shared_install_state.clone();
-    receiver.attach(None, move |installer| {
+    let rec_shared_config_state = shared_config_state.clone();
+    receiver.attach(None, move |(installer, kept_files)| {
         release_thread_name();
         set_installed_state(&rec_gui_data, false, &installer);
         let map_id = installer.map_id();
         rec_shared_install_state.borrow_mut().remove_map(map_id);
+        let is_custom = installer
+            .installed_map_pack()
+            .as_ref()
+            .is_some_and(|map_pack| map_pack.is_custom());
+        if is_custom {
+            remove_custom_entry(&rec_gui_data, map_id);
+        }
+        if !kept_files.is_empty() {
+            let config = rec_shared_config_state.borrow();
+            let locale = config.current_locale();
+            let text = format!(
+                "{}\n{}",
+                locale.uninstall_kept_files_text(),
+                kept_files.join("\n")
+            );
+            rec_gui_data.output_dialog.show_text(&text, locale);
+        }
         Continue(true)
     });
 
@@ -78,6 +77,7 @@ pub fn connect_uninstall_map(gui_data: &GuiData) {
             .to_string();
         let download_dir = shared_config_state.borrow().download_dir().to_owned();
         let quake_dir = shared_config_state.borrow().quake_dir().to_owned();
+        let map_pack = shared_install_state.borrow().get_map(&map_id).cloned();
 
         let sender = sender.clone();
         let thread_name = get_thread_name("uninstall");
@@ -88,40 +88,19 @@ pub fn connect_uninstall_map(gui_data: &GuiData) {
                     .with_download_dir(download_dir)
                     .with_quake_dir(quake_dir)
                     .with_path_string(path_string)
-                    .with_map_id(map_id);
-                installer.uninstall_map();
-                sender.send(installer).expect("Couldn't send");
+                    .with_map_id(map_id)
+                    .with_installed_map_pack(map_pack);
+                let kept_files = installer.uninstall_map(false);
+                sender.send((installer, kept_files)).expect("Couldn't send");
             })
             .expect("Failed to spawn install thread");
     });
 }
 
 pub fn connect_play_button(gui_data: &GuiData) {
-    let (sender, receiver): (Sender<Output>, Receiver<Output>) =
-        MainContext::channel(PRIORITY_DEFAULT);
     let button = gui_data.detail_pane.btn_play.clone();
     let gui_data = gui_data.clone();
     let dropdown = gui_data.detail_pane.dropdown.clone();
-    let output_dialog = gui_data.output_dialog.clone();
-    let dlg_output = output_dialog.dlg_output.clone();
-    let output_text = gui_data.output_dialog.txt_output.clone();
-    let shared_files_state = gui_data.shared_files_state.clone();
-    let shared_config_state = gui_data.shared_config_state.clone();
-    let con_shared_config_state = gui_data.shared_config_state.clone();
-    receiver.attach(None, move |result| {
-        let text: String = result
-            .stdout
-            .iter()
-            .map(|b| match b.is_ascii() {
-                true => *b as char,
-                false => ' ',
-            })
-            .collect();
-        output_text.get_buffer().unwrap().set_text(&text);
-        output_dialog.init_text(shared_config_state.borrow().current_locale());
-        dlg_output.show_all();
-        Continue(true)
-    });
     button.connect_clicked(move |_| {
         let model = dropdown.get_model().unwrap();
         let start_map = match dropdown.get_active_iter() {
@@ -134,9 +113,8 @@ pub fn connect_play_button(gui_data: &GuiData) {
         };
         let map_id =
             get_selected_map_id(&gui_data).expect("Nothing was selected, so this shouldn't happen");
-        let quake_exe = con_shared_config_state.borrow().quake_exe().to_owned();
-        let quake_dir = con_shared_config_state.borrow().quake_dir().to_owned();
-        let command_line_opt = shared_files_state
+        let database_line = gui_data
+            .shared_files_state
             .borrow()
             .iter()
             .find(|file| file.id() == &map_id)
@@ -144,130 +122,249 @@ pub fn connect_play_button(gui_data: &GuiData) {
             .tech_info()
             .command_line()
             .to_owned();
-        let sender = sender.clone();
-        let thread_name = get_thread_name("play");
-        thread::Builder::new()
-            .name(thread_name)
-            .spawn(move || {
-                let game_player = GamePlayerBuilder::default()
-                    .quake_exe(quake_exe)
-                    .quake_dir(quake_dir)
-                    .map_id(map_id)
-                    .start_map(start_map)
-                    .command_line(command_line_opt)
-                    .build()
-                    .unwrap();
-                let out = game_player.play_quake_map();
-                sender.send(out).expect("Couldn't send");
-            })
-            .expect("Failed to spawn play thread");
+        let (map_options, engine_name) =
+            match gui_data.shared_install_state.borrow().get_map(&map_id) {
+                Some(map_pack) => (
+                    map_pack.launch_options().to_owned(),
+                    map_pack.engine().to_owned(),
+                ),
+                None => (String::new(), String::new()),
+            };
+        let config = gui_data.shared_config_state.borrow();
+        let launch_args = LaunchArgsBuilder::default()
+            .engine(config.get_engine(&engine_name))
+            .map_id(map_id)
+            .start_map(start_map)
+            .database_line(database_line)
+            .user_options(config.launch_options().to_owned())
+            .map_options(map_options.clone())
+            .build()
+            .unwrap();
+        drop(config);
+        gui_data.shared_launch_state.replace(Some(launch_args));
+        let launch_dialog = &gui_data.launch_dialog;
+        // fires the changed handler, which fills in the command
+        launch_dialog.ent_launch_map_options.set_text(&map_options);
+        show_launch_command(&gui_data);
+        launch_dialog.dlg_launch.show_all();
     });
 }
 
-// Here to share the install map logic
-pub fn connect_install_random_map(gui_data: &GuiData) {
-    let (sender, receiver): (Sender<Installer>, Receiver<Installer>) =
+/// The confirmation shown before a map is started. Editing the options saves them for the map.
+pub fn connect_launch_dialog(gui_data: &GuiData) {
+    let (sender, receiver): (Sender<GameOutput>, Receiver<GameOutput>) =
         MainContext::channel(PRIORITY_DEFAULT);
-    let (progress_sender, progress_receiver): (
-        Sender<DownloadProgress>,
-        Receiver<DownloadProgress>,
-    ) = MainContext::channel(PRIORITY_DEFAULT);
-    let button = gui_data.filter_bar.btn_install_random.clone();
-    let detail_pane = gui_data.detail_pane.clone();
+    let launch_dialog = gui_data.launch_dialog.clone();
     let rec_gui_data = gui_data.clone();
-    let shared_install_state = rec_gui_data.shared_install_state.clone();
-    receiver.attach(None, move |installer| {
-        release_thread_name();
-        set_installed_state(&rec_gui_data, true, &installer);
-        let map_pack = installer.installed_map_pack().clone().unwrap();
-        shared_install_state.borrow_mut().add_map(map_pack);
+    receiver.attach(None, move |output| {
+        let output_dialog = &rec_gui_data.output_dialog;
+        match output {
+            GameOutput::Stdout(line) | GameOutput::Stderr(line) => output_dialog.append_line(&line),
+            GameOutput::Exited(code) => {
+                rec_gui_data.shared_game_state.replace(None);
+                let config = rec_gui_data.shared_config_state.borrow();
+                let locale = config.current_locale();
+                let text = match code {
+                    Some(code) => locale.game_exited_text().replace("{}", &code.to_string()),
+                    None => locale.game_killed_text().to_owned(),
+                };
+                output_dialog.set_game_status(&text);
+            }
+        }
         Continue(true)
     });
-    progress_receiver.attach(None, move |dl_progress| {
-        detail_pane.update_progress_bar(&dl_progress.file_name(), *dl_progress.percent());
-        Continue(true)
+
+    let con_gui_data = gui_data.clone();
+    launch_dialog
+        .ent_launch_map_options
+        .connect_changed(move |entry| {
+            let options = entry.get_buffer().get_text();
+            if let Some(launch_args) = con_gui_data.shared_launch_state.borrow_mut().as_mut() {
+                launch_args.set_map_options(options);
+            }
+            show_launch_command(&con_gui_data);
+        });
+
+    let con_gui_data = gui_data.clone();
+    launch_dialog.btn_launch_cancel.connect_clicked(move |_| {
+        con_gui_data.shared_launch_state.replace(None);
+        con_gui_data.launch_dialog.dlg_launch.hide();
+    });
+
+    let con_gui_data = gui_data.clone();
+    launch_dialog.btn_launch_ok.connect_clicked(move |_| {
+        let launch_args = match con_gui_data.shared_launch_state.replace(None) {
+            Some(launch_args) => launch_args,
+            None => return,
+        };
+        // the button is only clickable when these parse
+        let argv = launch_args.to_argv().expect("Launch options didn't parse");
+        con_gui_data.launch_dialog.dlg_launch.hide();
+        let map_id = launch_args.map_id().to_owned();
+        con_gui_data
+            .shared_install_state
+            .borrow_mut()
+            .set_launch_options(&map_id, launch_args.map_options().trim().to_owned());
+        let engine = launch_args.engine().clone();
+        let game_player = GamePlayerBuilder::default()
+            .quake_exe(engine.quake_exe().to_owned())
+            .working_dir(engine.quake_dir().to_owned())
+            .map_id(map_id.clone())
+            .args(argv)
+            .build()
+            .unwrap();
+        let config = con_gui_data.shared_config_state.borrow();
+        let locale = config.current_locale();
+        let output_dialog = &con_gui_data.output_dialog;
+        let log_path = session_log_path(config.logs_dir(), &map_id);
+        // the glib sender can't be shared between the reader threads on its own
+        let sender = Mutex::new(sender.clone());
+        let on_output = move |output| {
+            if let Err(e) = sender.lock().unwrap().send(output) {
+                // the window is gone, nothing left to show it in
+                debug!("Dropping game output: {}", e);
+            }
+        };
+        match game_player.launch(Some(&log_path), on_output) {
+            Ok(game) => {
+                output_dialog.start_game(&game_player.command_line(), locale);
+                con_gui_data.shared_game_state.replace(Some(game));
+            }
+            Err(e) => {
+                error!("Couldn't start {}: {}", engine.quake_exe(), e);
+                let text = locale
+                    .game_start_failed_text()
+                    .replace("{}", &e.to_string());
+                output_dialog.show_text(&text, locale);
+            }
+        }
     });
+}
+
+/// Remembers the engine picked for the selected map.
+pub fn connect_engine_choice(gui_data: &GuiData) {
+    let engine_dropdown = gui_data.detail_pane.engine_dropdown.clone();
     let con_gui_data = gui_data.clone();
+    engine_dropdown.connect_changed(move |dropdown| {
+        // also fires while the list is being filled in for a new selection
+        let name = match dropdown.get_active_id() {
+            Some(name) => name.to_string(),
+            None => return,
+        };
+        let map_id = match get_selected_map_id(&con_gui_data) {
+            Some(map_id) => map_id,
+            None => return,
+        };
+        let name = match name == DEFAULT_ENGINE_NAME {
+            true => String::new(),
+            false => name,
+        };
+        let mut local_maps = con_gui_data.shared_install_state.borrow_mut();
+        let is_changed = match local_maps.get_map(&map_id) {
+            Some(map_pack) => map_pack.engine() != &name,
+            None => false,
+        };
+        if is_changed {
+            info!("Playing {} with engine '{}' from now on", map_id, name);
+            local_maps.set_engine(&map_id, name);
+        }
+    });
+}
 
+fn show_launch_command(gui_data: &GuiData) {
+    let launch_args = gui_data.shared_launch_state.borrow();
+    let launch_args = match launch_args.as_ref() {
+        Some(launch_args) => launch_args,
+        None => return,
+    };
+    let config = gui_data.shared_config_state.borrow();
+    let command = match launch_args.to_argv() {
+        Ok(argv) => {
+            let mut full_argv = vec![launch_args.engine().quake_exe().to_owned()];
+            full_argv.extend(argv);
+            Ok(join_args(&full_argv))
+        }
+        Err(e) => Err(config
+            .current_locale()
+            .launch_options_invalid_text()
+            .replace("{}", &e.to_string())),
+    };
+    gui_data.launch_dialog.set_command(command);
+}
+
+pub fn connect_verify_button(gui_data: &GuiData) {
+    let button = gui_data.detail_pane.btn_verify.clone();
+    let con_gui_data = gui_data.clone();
     button.connect_clicked(move |_| {
-        trace!("Install random button clicked");
-        let map_list_state = con_gui_data.shared_files_state.clone();
-        let map_list = map_list_state.borrow();
-        let installed_map_list = con_gui_data.shared_install_state.clone();
-        let uninstalled_maps: Vec<&QuakeFile> = map_list
-            .iter()
-            .filter(|map| !installed_map_list.borrow().is_map_installed(map.id()))
-            .collect();
-        let mut rng = thread_rng();
-        let map_choice = rng.gen_range(0..uninstalled_maps.len());
-        let map_id = uninstalled_maps
-            .get(map_choice)
-            .expect("Map choice index is out of bounds")
-            .id();
-        select_map_by_id(&con_gui_data, &map_id);
-        install_map(
-            &con_gui_data,
-            progress_sender.clone(),
-            sender.clone(),
-            map_id.to_owned(),
-        );
+        trace!("Verify button clicked");
+        let map_id = get_selected_map_id(&con_gui_data)
+            .expect("Nothing was selected, so this shouldn't happen");
+        let map_pack = con_gui_data
+            .shared_install_state
+            .borrow()
+            .get_map(&map_id)
+            .cloned();
+        match map_pack {
+            Some(map_pack) => check_installs(&con_gui_data, vec![map_pack]),
+            None => warn!("{} isn't in the installed list", map_id),
+        }
     });
 }
 
-fn install_map(
-    gui_data: &GuiData,
-    progress_sender: Sender<DownloadProgress>,
-    install_sender: Sender<Installer>,
-    map_id: String,
-) {
-    let path_string = get_path_string_from_id(gui_data, &map_id);
-    let tree_view = gui_data.list_view.tree_view.clone();
-    let column = tree_view
-        .get_column(0)
-        .expect("No zero column for some reason");
-    tree_view.scroll_to_cell(
-        Some(&gtk::TreePath::from_string(&path_string)),
-        Some(&column),
-        false,
-        0.0,
-        0.5,
-    );
-    let shared_config_state = gui_data.shared_config_state.clone();
-    let download_dir = shared_config_state.borrow().download_dir().to_owned();
-    let quake_dir = shared_config_state.borrow().quake_dir().to_owned();
-    let mut detail_pane = gui_data.detail_pane.clone();
-    detail_pane.add_progress_bar(&map_id);
-    let progress_sender = progress_sender.clone();
-    let thread_name = get_thread_name("install");
-    thread::Builder::new()
-        .name(thread_name)
-        .spawn(move || {
-            let mut installer = Installer::new()
-                .with_download_dir(download_dir)
-                .with_quake_dir(quake_dir)
-                .with_map_id(map_id)
-                .with_path_string(path_string);
-            installer.install_map(progress_sender);
-            install_sender.send(installer).expect("Couldn't send");
-        })
-        .expect("Failed to spawn install thread");
+pub fn connect_install_random_map(gui_data: &GuiData) {
+    let button = gui_data.filter_bar.btn_install_random.clone();
+    let con_gui_data = gui_data.clone();
+
+    button.connect_clicked(move |_| {
+        trace!("Install random button clicked");
+        let map_id = {
+            let map_list = con_gui_data.shared_files_state.borrow();
+            let installed_map_list = con_gui_data.shared_install_state.borrow();
+            let uninstalled_maps: Vec<&QuakeFile> = map_list
+                .iter()
+                .filter(|map| !installed_map_list.is_map_installed(map.id()))
+                .collect();
+            let mut rng = thread_rng();
+            let map_choice = rng.gen_range(0..uninstalled_maps.len());
+            uninstalled_maps
+                .get(map_choice)
+                .expect("Map choice index is out of bounds")
+                .id()
+                .to_owned()
+        };
+        select_map_by_id(&con_gui_data, &map_id);
+        enqueue_install(&con_gui_data, map_id);
+    });
 }
 
-fn set_installed_state(gui_data: &GuiData, is_local: bool, installer: &Installer) {
+pub fn set_installed_state(gui_data: &GuiData, is_local: bool, installer: &Installer) {
     let config_state = gui_data.shared_config_state.clone();
     if let Some(current_path_string) = get_current_path_string(gui_data) {
         if installer.path_string() == &current_path_string {
             let install_button = gui_data.detail_pane.btn_install.clone();
             let uninstall_button = gui_data.detail_pane.btn_uninstall.clone();
             let play_button = gui_data.detail_pane.btn_play.clone();
+            let verify_button = gui_data.detail_pane.btn_verify.clone();
             install_button.set_sensitive(!is_local && !config_state.borrow().is_offline());
             uninstall_button.set_sensitive(is_local);
             play_button.set_sensitive(is_local);
+            verify_button.set_sensitive(is_local);
+            let engine_dropdown = gui_data.detail_pane.engine_dropdown.clone();
+            engine_dropdown.set_sensitive(is_local && !config_state.borrow().engines().is_empty());
         }
     }
     update_list(&gui_data, is_local, installer.path_string());
 }
 
+pub fn add_dependencies_to_list(gui_data: &GuiData, installer: &Installer) {
+    let shared_install_state = gui_data.shared_install_state.clone();
+    for map_pack in installer.installed_dependencies() {
+        let path_string = get_path_string_from_id(gui_data, map_pack.id());
+        update_list(gui_data, true, &path_string);
+        shared_install_state.borrow_mut().add_map(map_pack.clone());
+    }
+}
+
 fn update_list(gui_data: &GuiData, is_local: bool, path_string: &String) {
     let list = gui_data.list_view.list_store.clone();
     match get_list_selection(gui_data, &path_string) {
@@ -328,7 +425,7 @@ fn release_thread_name() {
     trace!("Thread counter: {:?}", THREAD_COUNTER);
 }
 
-fn get_path_string_from_id(gui_data: &GuiData, id: &String) -> String {
+pub fn get_path_string_from_id(gui_data: &GuiData, id: &String) -> String {
     let tree_view = gui_data.list_view.tree_view.clone();
     let tree_model = tree_view.get_model().unwrap();
     let mut path_string = String::new();
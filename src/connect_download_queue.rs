@@ -0,0 +1,310 @@
+use crate::channel_reporter::ChannelReporter;
+use crate::connect_detail_buttons::{
+    add_dependencies_to_list, get_path_string_from_id, set_installed_state,
+};
+use crate::dependencies::resolve_dependencies;
+use crate::download_error::DownloadError;
+use crate::download_progress::DownloadProgress;
+use crate::download_queue::{QueueItem, QueueState};
+use crate::gui_data::GuiData;
+use crate::installer::Installer;
+use crate::quake_paths::MissionPack;
+use crate::queue_panel::QueueRow;
+use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
+use gtk::prelude::*;
+use gtk::{ButtonsType, DialogFlags, MessageDialog, MessageType, ResponseType};
+use log::*;
+use std::thread;
+
+type InstallResult = (Installer, Result<(), DownloadError>);
+
+pub fn enqueue_install(gui_data: &GuiData, map_id: String) {
+    if !confirm_mission_packs(gui_data, &map_id) {
+        return;
+    }
+    let dependency_ids = {
+        let files = gui_data.shared_files_state.borrow();
+        let local_maps = gui_data.shared_install_state.borrow();
+        // only used to keep jobs apart, a bad requirement fails once the job starts
+        resolve_dependencies(&map_id, &files, &local_maps)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| file.id().to_owned())
+            .collect()
+    };
+    let is_queued = gui_data
+        .shared_queue_state
+        .borrow_mut()
+        .enqueue(&map_id, dependency_ids);
+    if !is_queued {
+        return;
+    }
+    scroll_to_map(gui_data, &map_id);
+    let is_new_row = !gui_data.queue_panel.has_row(&map_id);
+    {
+        let config = gui_data.shared_config_state.borrow();
+        let locale = config.current_locale();
+        let row = gui_data.queue_panel.add_row(&map_id, locale);
+        if is_new_row {
+            connect_row_buttons(gui_data, &row);
+        }
+        gui_data
+            .queue_panel
+            .update_state(&map_id, &QueueState::Queued, locale);
+    }
+    start_pending(gui_data);
+}
+
+// asks before installing something that won't run without a mission pack that isn't there
+fn confirm_mission_packs(gui_data: &GuiData, map_id: &String) -> bool {
+    let missing = {
+        let files = gui_data.shared_files_state.borrow();
+        let local_maps = gui_data.shared_install_state.borrow();
+        let config = gui_data.shared_config_state.borrow();
+        // whatever's wrong with the requirements is reported once the install starts
+        let mut needed = resolve_dependencies(map_id, &files, &local_maps).unwrap_or_default();
+        if let Some(file) = files.iter().find(|f| f.id() == map_id) {
+            needed.push(file.clone());
+        }
+        let mut missing: Vec<MissionPack> = vec![];
+        for pack in needed
+            .iter()
+            .flat_map(|file| config.missing_mission_packs(file))
+        {
+            if !missing.contains(&pack) {
+                missing.push(pack);
+            }
+        }
+        missing
+    };
+    if missing.is_empty() {
+        return true;
+    }
+    warn!(
+        "{} needs mission packs that aren't installed: {:?}",
+        map_id, missing
+    );
+    let text = {
+        let config = gui_data.shared_config_state.borrow();
+        let names: Vec<String> = missing.iter().map(|pack| pack.to_string()).collect();
+        config
+            .current_locale()
+            .mission_pack_missing_text()
+            .replacen("{}", map_id, 1)
+            .replacen("{}", &names.join(", "), 1)
+    };
+    let dialog = MessageDialog::new(
+        Some(&gui_data.window),
+        DialogFlags::MODAL,
+        MessageType::Warning,
+        ButtonsType::YesNo,
+        &text,
+    );
+    let response = dialog.run();
+    dialog.close();
+    response == ResponseType::Yes
+}
+
+/// Starts as many waiting installs as the concurrency limit allows.
+pub fn start_pending(gui_data: &GuiData) {
+    loop {
+        let next = gui_data.shared_queue_state.borrow_mut().start_next();
+        match next {
+            Some(item) => start_job(gui_data, item),
+            None => break,
+        }
+    }
+}
+
+fn start_job(gui_data: &GuiData, item: QueueItem) {
+    let map_id = item.map_id().to_owned();
+    let (dependencies, md5, zip_base_dir) = {
+        let files = gui_data.shared_files_state.borrow();
+        let local_maps = gui_data.shared_install_state.borrow();
+        let (md5, zip_base_dir) = match files.iter().find(|f| f.id() == &map_id) {
+            Some(file) => (
+                file.md5().to_owned(),
+                file.tech_info().zip_base_dir().clone(),
+            ),
+            None => (String::new(), None),
+        };
+        match resolve_dependencies(&map_id, &files, &local_maps) {
+            Ok(deps) => (deps, md5, zip_base_dir),
+            Err(e) => {
+                error!("Can't install {}: {}", map_id, e);
+                finish_job(gui_data, &map_id, QueueState::Failed(e.to_string()));
+                return;
+            }
+        }
+    };
+    {
+        let config = gui_data.shared_config_state.borrow();
+        gui_data
+            .queue_panel
+            .update_state(&map_id, &QueueState::Running, config.current_locale());
+    }
+    let (sender, receiver): (Sender<InstallResult>, Receiver<InstallResult>) =
+        MainContext::channel(PRIORITY_DEFAULT);
+    let (progress_sender, progress_receiver): (
+        Sender<DownloadProgress>,
+        Receiver<DownloadProgress>,
+    ) = MainContext::channel(PRIORITY_DEFAULT);
+
+    let queue_panel = gui_data.queue_panel.clone();
+    let progress_map_id = map_id.clone();
+    progress_receiver.attach(None, move |dl_progress| {
+        // errors are reported once the installer hands back its result
+        if dl_progress.error().is_none() {
+            let file_name = dl_progress.file_name();
+            queue_panel.update_progress(&progress_map_id, file_name, *dl_progress.percent());
+            if *dl_progress.resumed_bytes() > 0 {
+                queue_panel.set_resumed(&progress_map_id, *dl_progress.resumed_bytes());
+            }
+        }
+        Continue(true)
+    });
+
+    let rec_gui_data = gui_data.clone();
+    receiver.attach(None, move |(installer, result)| {
+        // dependencies that made it in before a failure are still on disk
+        add_dependencies_to_list(&rec_gui_data, &installer);
+        if let Some(map_pack) = installer.installed_map_pack().clone() {
+            set_installed_state(&rec_gui_data, true, &installer);
+            rec_gui_data
+                .shared_install_state
+                .borrow_mut()
+                .add_map(map_pack);
+        }
+        let state = match result {
+            Ok(()) => QueueState::Done,
+            Err(DownloadError::Cancelled) => QueueState::Cancelled,
+            Err(e) => QueueState::Failed(e.to_string()),
+        };
+        finish_job(&rec_gui_data, installer.map_id(), state);
+        start_pending(&rec_gui_data);
+        Continue(false)
+    });
+
+    let path_string = get_path_string_from_id(gui_data, &map_id);
+    let download_dir = gui_data
+        .shared_config_state
+        .borrow()
+        .download_dir()
+        .to_owned();
+    let quake_dir = gui_data.shared_config_state.borrow().quake_dir().to_owned();
+    let mirrors = gui_data.shared_config_state.borrow().mirrors().clone();
+    let cancel_flag = item.cancel_flag().clone();
+    thread::Builder::new()
+        .name(format!("install-{}", map_id))
+        .spawn(move || {
+            let mut installer = Installer::new()
+                .with_download_dir(download_dir)
+                .with_quake_dir(quake_dir)
+                .with_map_id(map_id)
+                .with_path_string(path_string)
+                .with_md5(md5)
+                .with_zip_base_dir(zip_base_dir)
+                .with_dependencies(dependencies)
+                .with_cancel_flag(cancel_flag)
+                .with_mirrors(mirrors);
+            let result = installer.install_map(&ChannelReporter::new(progress_sender));
+            if let Err(e) = &result {
+                warn!("Install of {} stopped: {}", installer.map_id(), e);
+            }
+            sender.send((installer, result)).expect("Couldn't send");
+        })
+        .expect("Failed to spawn install thread");
+}
+
+fn finish_job(gui_data: &GuiData, map_id: &String, state: QueueState) {
+    let is_done = state == QueueState::Done;
+    {
+        let config = gui_data.shared_config_state.borrow();
+        gui_data
+            .queue_panel
+            .update_state(map_id, &state, config.current_locale());
+    }
+    let mut queue = gui_data.shared_queue_state.borrow_mut();
+    queue.finish(map_id, state);
+    // finished installs drop off the list, failures stay so they can be retried
+    if is_done {
+        queue.remove(map_id);
+        gui_data.queue_panel.remove_row(map_id);
+    }
+}
+
+fn connect_row_buttons(gui_data: &GuiData, row: &QueueRow) {
+    let cancel_gui_data = gui_data.clone();
+    let cancel_map_id = row.map_id.clone();
+    row.btn_cancel.connect_clicked(move |_| {
+        trace!("Queue cancel clicked for {}", cancel_map_id);
+        let gui_data = &cancel_gui_data;
+        let state = match gui_data
+            .shared_queue_state
+            .borrow()
+            .get_item(&cancel_map_id)
+        {
+            Some(item) => item.state().clone(),
+            None => return,
+        };
+        if state.is_finished() {
+            gui_data
+                .shared_queue_state
+                .borrow_mut()
+                .remove(&cancel_map_id);
+            gui_data.queue_panel.remove_row(&cancel_map_id);
+            return;
+        }
+        gui_data
+            .shared_queue_state
+            .borrow_mut()
+            .cancel(&cancel_map_id);
+        // a running install only stops once its thread notices the flag
+        if state == QueueState::Queued {
+            let config = gui_data.shared_config_state.borrow();
+            gui_data.queue_panel.update_state(
+                &cancel_map_id,
+                &QueueState::Cancelled,
+                config.current_locale(),
+            );
+        }
+    });
+
+    let retry_gui_data = gui_data.clone();
+    let retry_map_id = row.map_id.clone();
+    row.btn_retry.connect_clicked(move |_| {
+        trace!("Queue retry clicked for {}", retry_map_id);
+        let gui_data = &retry_gui_data;
+        if !gui_data
+            .shared_queue_state
+            .borrow_mut()
+            .retry(&retry_map_id)
+        {
+            return;
+        }
+        {
+            let config = gui_data.shared_config_state.borrow();
+            gui_data.queue_panel.update_state(
+                &retry_map_id,
+                &QueueState::Queued,
+                config.current_locale(),
+            );
+        }
+        start_pending(gui_data);
+    });
+}
+
+fn scroll_to_map(gui_data: &GuiData, map_id: &String) {
+    let path_string = get_path_string_from_id(gui_data, map_id);
+    let tree_view = gui_data.list_view.tree_view.clone();
+    let column = tree_view
+        .get_column(0)
+        .expect("No zero column for some reason");
+    tree_view.scroll_to_cell(
+        Some(&gtk::TreePath::from_string(&path_string)),
+        Some(&column),
+        false,
+        0.0,
+        0.5,
+    );
+}
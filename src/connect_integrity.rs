@@ -0,0 +1,173 @@
+use crate::channel_reporter::ChannelReporter;
+use crate::configuration::MapPack;
+use crate::download_error::DownloadError;
+use crate::download_progress::DownloadProgress;
+use crate::gui_data::GuiData;
+use crate::installer::Installer;
+use crate::integrity::{verify_installation, IntegrityReport};
+use crate::locales::Locale;
+use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
+use gtk::prelude::*;
+use gtk::{ButtonsType, DialogFlags, MessageDialog, MessageType, ResponseType};
+use log::*;
+use std::thread;
+
+/// Verifies the given maps off the main thread and offers to repair any that are broken.
+pub fn check_installs(gui_data: &GuiData, map_packs: Vec<MapPack>) {
+    let quake_dir = gui_data.shared_config_state.borrow().quake_dir().clone();
+    let (sender, receiver): (Sender<Vec<IntegrityReport>>, Receiver<Vec<IntegrityReport>>) =
+        MainContext::channel(PRIORITY_DEFAULT);
+    thread::Builder::new()
+        .name("verify-installs".to_string())
+        .spawn(move || {
+            let reports = map_packs
+                .iter()
+                .map(|map_pack| verify_installation(&quake_dir, map_pack))
+                .collect();
+            sender.send(reports).expect("Failed to send");
+        })
+        .expect("Failed to spawn thread");
+    let gui_data = gui_data.clone();
+    receiver.attach(None, move |reports| {
+        show_reports(&gui_data, reports);
+        Continue(false)
+    });
+}
+
+fn show_reports(gui_data: &GuiData, reports: Vec<IntegrityReport>) {
+    let broken_ids: Vec<String> = reports
+        .iter()
+        .filter(|report| report.needs_repair())
+        .map(|report| report.map_id().to_owned())
+        .collect();
+    let text = {
+        let config = gui_data.shared_config_state.borrow();
+        let locale = config.current_locale();
+        let lines: Vec<String> = reports
+            .iter()
+            .filter(|report| !report.is_clean())
+            .map(|report| format_report(report, locale))
+            .collect();
+        if lines.is_empty() {
+            gui_data
+                .output_dialog
+                .show_text(locale.integrity_all_intact_text(), locale);
+            return;
+        }
+        if broken_ids.is_empty() {
+            gui_data.output_dialog.show_text(&lines.join("\n"), locale);
+            return;
+        }
+        format!(
+            "{}\n\n{}",
+            lines.join("\n"),
+            locale.integrity_repair_question()
+        )
+    };
+    let dialog = MessageDialog::new(
+        Some(&gui_data.window),
+        DialogFlags::MODAL,
+        MessageType::Question,
+        ButtonsType::YesNo,
+        &text,
+    );
+    let response = dialog.run();
+    dialog.hide();
+    if response == ResponseType::Yes {
+        broken_ids
+            .iter()
+            .for_each(|map_id| repair_map(gui_data, map_id));
+    }
+}
+
+fn format_report(report: &IntegrityReport, locale: &Locale) -> String {
+    let mut lines = vec![format!("{}:", report.map_id())];
+    if *report.is_untracked() {
+        lines.push(format!("    {}", locale.integrity_untracked_text()));
+    }
+    let groups = [
+        (locale.integrity_modified_text(), report.modified()),
+        (locale.integrity_missing_text(), report.missing()),
+        (locale.integrity_extra_text(), report.extra()),
+    ];
+    for (label, files) in groups.iter() {
+        for file in files.iter() {
+            lines.push(format!("    {}: {}", label, file));
+        }
+    }
+    lines.join("\n")
+}
+
+type RepairResult = (Installer, Result<(), DownloadError>);
+
+fn repair_map(gui_data: &GuiData, map_id: &String) {
+    let map_pack = gui_data
+        .shared_install_state
+        .borrow()
+        .get_map(map_id)
+        .cloned();
+    let (md5, zip_base_dir) = match gui_data
+        .shared_files_state
+        .borrow()
+        .iter()
+        .find(|file| file.id() == map_id)
+    {
+        Some(file) => (
+            file.md5().to_owned(),
+            file.tech_info().zip_base_dir().clone(),
+        ),
+        None => (String::new(), None),
+    };
+    let download_dir = gui_data.shared_config_state.borrow().download_dir().clone();
+    let quake_dir = gui_data.shared_config_state.borrow().quake_dir().clone();
+    let mirrors = gui_data.shared_config_state.borrow().mirrors().clone();
+    let (sender, receiver): (Sender<RepairResult>, Receiver<RepairResult>) =
+        MainContext::channel(PRIORITY_DEFAULT);
+    let (progress_sender, progress_receiver): (
+        Sender<DownloadProgress>,
+        Receiver<DownloadProgress>,
+    ) = MainContext::channel(PRIORITY_DEFAULT);
+    progress_receiver.attach(None, |dl_progress| {
+        trace!("Repair progress: {:?}", dl_progress);
+        Continue(true)
+    });
+    let map_id = map_id.to_owned();
+    thread::Builder::new()
+        .name(format!("repair-{}", map_id))
+        .spawn(move || {
+            let mut installer = Installer::new()
+                .with_download_dir(download_dir)
+                .with_quake_dir(quake_dir)
+                .with_map_id(map_id)
+                .with_md5(md5)
+                .with_zip_base_dir(zip_base_dir)
+                .with_installed_map_pack(map_pack)
+                .with_mirrors(mirrors);
+            let result = installer.repair_map(&ChannelReporter::new(progress_sender));
+            sender.send((installer, result)).expect("Couldn't send");
+        })
+        .expect("Failed to spawn repair thread");
+    let gui_data = gui_data.clone();
+    receiver.attach(None, move |(installer, result)| {
+        let config = gui_data.shared_config_state.borrow();
+        let locale = config.current_locale();
+        let text = match result {
+            Ok(()) => {
+                let map_pack = installer
+                    .installed_map_pack()
+                    .clone()
+                    .expect("Repair finished without a map pack");
+                let mut local_maps = gui_data.shared_install_state.borrow_mut();
+                local_maps.replace_map(map_pack);
+                format!(
+                    "{}: {}",
+                    installer.map_id(),
+                    locale.integrity_repaired_text()
+                )
+            }
+            Err(e) => format!("{}: {}", installer.map_id(), e),
+        };
+        gui_data.output_dialog.show_text(&text, locale);
+        Continue(false)
+    });
+}
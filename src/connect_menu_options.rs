@@ -1,6 +1,11 @@
+use crate::connect_integrity::check_installs;
+use crate::database_refresh::{is_database_stale, refresh_database, DatabaseDiff, DatabaseRefresh};
+use crate::download_error::DownloadError;
 use crate::gui_data::GuiData;
+use crate::installer::{verify_cached_zips, ZipStatus};
 use crate::list_view::populate_list_view;
-use crate::request_utils::get_database_from_remote;
+use crate::locales::Locale;
+use crate::quake_file::BACKUP_FILE_NAME;
 use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
 use gtk::prelude::*;
 use gtk::AccelGroup;
@@ -37,34 +42,109 @@ pub fn connect_reload(gui_data: &GuiData) {
     trace!("Initializing reload connection");
     let menu_reload = gui_data.main_menu.menu_reload.clone();
     let gui_data = gui_data.clone();
-    let shared_configs = gui_data.shared_config_state.clone();
     menu_reload.connect_activate(move |_| {
         info!("Database reload request made");
-        let mut config_dir = shared_configs.borrow().config_dir().clone();
-        config_dir.push("database.xml");
-        let (sender, receiver): (Sender<bool>, Receiver<bool>) =
-            MainContext::channel(PRIORITY_DEFAULT);
-        thread::Builder::new()
-            .name("Reload-0".to_string())
-            .spawn(move || {
-                get_database_from_remote(config_dir);
-                sender.send(true).expect("Failed to send");
-            })
-            .expect("Failed to spawn thread");
-        let rec_gui_data = gui_data.clone();
-        receiver.attach(None, move |result| {
-            if result {
+        start_database_refresh(&gui_data, true);
+    });
+}
+
+pub fn connect_database_retry(gui_data: &GuiData) {
+    trace!("Initializing database retry connection");
+    let btn_retry = gui_data.database_error_bar.btn_database_retry.clone();
+    let gui_data = gui_data.clone();
+    btn_retry.connect_clicked(move |_| {
+        info!("Database retry request made");
+        gui_data.database_error_bar.hide();
+        populate_list_view(&gui_data);
+    });
+}
+
+/// Refreshes the database in the background when the last fetch is older than the configured
+/// max age.
+pub fn refresh_database_if_stale(gui_data: &GuiData) {
+    let config = gui_data.shared_config_state.borrow();
+    let max_age_hours = *config.database_max_age_hours();
+    if *config.is_offline() || !is_database_stale(config.config_dir(), max_age_hours) {
+        return;
+    }
+    drop(config);
+    start_database_refresh(gui_data, false);
+}
+
+type RefreshResult = Result<DatabaseRefresh, DownloadError>;
+
+// an unprompted refresh only speaks up when something changed
+fn start_database_refresh(gui_data: &GuiData, is_requested: bool) {
+    let config_dir = gui_data.shared_config_state.borrow().config_dir().clone();
+    let mirrors = gui_data.shared_config_state.borrow().mirrors().clone();
+    let (sender, receiver): (Sender<RefreshResult>, Receiver<RefreshResult>) =
+        MainContext::channel(PRIORITY_DEFAULT);
+    thread::Builder::new()
+        .name("Reload-0".to_string())
+        .spawn(move || {
+            let result = refresh_database(&config_dir, &mirrors);
+            sender.send(result).expect("Failed to send");
+        })
+        .expect("Failed to spawn thread");
+    let rec_gui_data = gui_data.clone();
+    receiver.attach(None, move |result| {
+        let config = rec_gui_data.shared_config_state.borrow();
+        let locale = config.current_locale();
+        match result {
+            Ok(DatabaseRefresh::Updated(diff)) => {
                 populate_list_view(&rec_gui_data);
+                rec_gui_data
+                    .output_dialog
+                    .show_text(&format_diff(&diff, locale), locale);
             }
-            Continue(true)
-        });
+            Ok(DatabaseRefresh::Unchanged) => {
+                if is_requested {
+                    rec_gui_data
+                        .output_dialog
+                        .show_text(locale.database_up_to_date_text(), locale);
+                }
+            }
+            Err(e) => {
+                error!("Database reload failed: {}", e);
+                if is_requested {
+                    rec_gui_data.output_dialog.show_text(&e.to_string(), locale);
+                }
+            }
+        }
+        Continue(false)
     });
 }
 
+fn format_diff(diff: &DatabaseDiff, locale: &Locale) -> String {
+    if diff.is_empty() {
+        return locale.database_up_to_date_text().to_owned();
+    }
+    let groups = [
+        (locale.database_added_text(), diff.added()),
+        (locale.database_updated_text(), diff.updated()),
+        (locale.database_removed_text(), diff.removed()),
+    ];
+    let mut sections = vec![];
+    for (label, files) in groups.iter() {
+        if files.is_empty() {
+            continue;
+        }
+        let mut lines = vec![format!("{} ({}):", label, files.len())];
+        lines.extend(
+            files
+                .iter()
+                .map(|file| format!("    {}: {}", file.id(), file.title())),
+        );
+        sections.push(lines.join("\n"));
+    }
+    sections.join("\n\n")
+}
+
 pub fn connect_offline(gui_data: &GuiData) {
     trace!("Initializing offline connection");
     let menu_reload = gui_data.main_menu.menu_reload.clone();
     let menu_offline = gui_data.main_menu.menu_offline.clone();
+    let menu_install_url = gui_data.main_menu.menu_install_url.clone();
     let config_state = gui_data.shared_config_state.clone();
     let install_state = gui_data.shared_install_state.clone();
     let tree_view = gui_data.list_view.tree_view.clone();
@@ -77,6 +157,7 @@ pub fn connect_offline(gui_data: &GuiData) {
         };
         info!("Switching to {} mode", msg);
         menu_reload.set_sensitive(!active_state);
+        menu_install_url.set_sensitive(!active_state);
         match tree_view.get_selection().get_selected() {
             Some((model, iter)) => {
                 let string_res: Result<Option<String>, glib::value::GetError> =
@@ -92,6 +173,65 @@ pub fn connect_offline(gui_data: &GuiData) {
     });
 }
 
+type CacheReport = Vec<(String, ZipStatus)>;
+
+pub fn connect_verify_cache(gui_data: &GuiData) {
+    trace!("Initializing cache verification");
+    let menu_verify_cache = gui_data.main_menu.menu_verify_cache.clone();
+    let gui_data = gui_data.clone();
+    menu_verify_cache.connect_activate(move |_| {
+        info!("Cache verification request made");
+        let download_dir = gui_data.shared_config_state.borrow().download_dir().clone();
+        let files = gui_data.shared_files_state.borrow().clone();
+        let (sender, receiver): (Sender<CacheReport>, Receiver<CacheReport>) =
+            MainContext::channel(PRIORITY_DEFAULT);
+        thread::Builder::new()
+            .name("Verify-0".to_string())
+            .spawn(move || {
+                let report = verify_cached_zips(&download_dir, &files);
+                sender.send(report).expect("Failed to send");
+            })
+            .expect("Failed to spawn thread");
+        let rec_gui_data = gui_data.clone();
+        receiver.attach(None, move |report| {
+            let config = rec_gui_data.shared_config_state.borrow();
+            let locale = config.current_locale();
+            let lines: Vec<String> = report
+                .iter()
+                .filter_map(|(map_id, status)| match status {
+                    ZipStatus::Corrupt(e) => Some(format!(
+                        "{}: {} ({})",
+                        map_id,
+                        locale.verify_cache_corrupt_text(),
+                        e
+                    )),
+                    ZipStatus::Stale => {
+                        Some(format!("{}: {}", map_id, locale.verify_cache_stale_text()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let text = match lines.is_empty() {
+                true => locale.verify_cache_all_valid_text().to_owned(),
+                false => lines.join("\n"),
+            };
+            rec_gui_data.output_dialog.show_text(&text, locale);
+            Continue(false)
+        });
+    });
+}
+
+pub fn connect_verify_installs(gui_data: &GuiData) {
+    trace!("Initializing install verification");
+    let menu_verify_installs = gui_data.main_menu.menu_verify_installs.clone();
+    let gui_data = gui_data.clone();
+    menu_verify_installs.connect_activate(move |_| {
+        info!("Install verification request made");
+        let map_packs = gui_data.shared_install_state.borrow().maps().clone();
+        check_installs(&gui_data, map_packs);
+    });
+}
+
 pub fn connect_clear_cache(gui_data: &GuiData) {
     trace!("Initializing cache clear");
     let menu_clear_cache = gui_data.main_menu.menu_clear_cache.clone();
@@ -116,6 +256,10 @@ pub fn connect_clear_cache_ok(gui_data: &GuiData) {
         clear_cache_dialog.hide();
         remove_file_with_name(&config_dir, "config.xml");
         remove_file_with_name(&config_dir, "database.xml");
+        remove_file_with_name(&config_dir, BACKUP_FILE_NAME);
+        remove_file_with_name(&config_dir, "database.xml.corrupt");
+        remove_file_with_name(&config_dir, "databaseMeta.xml");
+        remove_file_with_name(&config_dir, "seenMaps.xml");
         remove_file_with_name(&config_dir, "installedMaps.xml");
         match remove_dir_all(&image_cache_dir) {
             Ok(_) => debug!("Removed images directory"),
@@ -13,3 +13,16 @@ pub fn connect_ok(gui_data: &GuiData) {
         dialog.hide();
     });
 }
+
+pub fn connect_kill_game(gui_data: &GuiData) {
+    trace!("Initializing kill game button");
+    let shared_game_state = gui_data.shared_game_state.clone();
+    let button = gui_data.output_dialog.btn_kill_game.clone();
+    button.connect_clicked(move |button| {
+        if let Some(game) = shared_game_state.borrow().as_ref() {
+            game.kill();
+        }
+        // the status changes once the game is actually gone
+        button.set_sensitive(false);
+    });
+}
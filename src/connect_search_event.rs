@@ -1,4 +1,5 @@
 use crate::gui_data::GuiData;
+use crate::whats_new::{find_changes, MapChange};
 use gtk::prelude::*;
 use log::*;
 
@@ -19,6 +20,13 @@ pub fn connect_search_event(gui_data: &GuiData) {
     });
 }
 
+/// Runs the current filter text and what's new toggle over the list again.
+pub fn refilter_list(gui_data: &GuiData) {
+    let search_entry = gui_data.filter_bar.entry_filter_text.clone();
+    let text = search_entry.get_buffer().get_text().trim().to_owned();
+    update_list(gui_data, text);
+}
+
 fn update_list(gui_data: &GuiData, text: String) {
     trace!("Updating list with text: {}", text);
     let tree_view = gui_data.list_view.tree_view.clone();
@@ -52,8 +60,32 @@ fn change_list_data(gui_data: &GuiData, text: String) {
     selection.unselect_all();
     clear_button.set_sensitive(text.len() > 0);
     let col_indices = [0, 1, 2, 3, 4, 5, 6];
+    let changes = match gui_data.filter_bar.btn_whats_new.get_active() {
+        true => Some(find_changes(
+            &shared_files_state.borrow(),
+            &gui_data.shared_seen_state.borrow(),
+        )),
+        false => None,
+    };
+    let locale = gui_data
+        .shared_config_state
+        .borrow()
+        .current_locale()
+        .clone();
     list.clear();
     shared_files_state.borrow().iter().for_each(|file| {
+        let title = match changes.as_ref().map(|changes| changes.get(file.id())) {
+            None => file.title().to_owned(),
+            Some(None) => return,
+            Some(Some(change)) => {
+                let change_text = match change {
+                    MapChange::Added => locale.whats_new_added_text(),
+                    MapChange::Updated => locale.whats_new_updated_text(),
+                    MapChange::Rerated => locale.whats_new_rerated_text(),
+                };
+                format!("{} ({})", file.title(), change_text)
+            }
+        };
         let valid_id = compare_text(file.id(), &text);
         let valid_title = compare_text(file.title(), &text);
         let valid_author = compare_text(file.author(), &text);
@@ -67,7 +99,7 @@ fn change_list_data(gui_data: &GuiData, text: String) {
             let values: [&dyn ToValue; 7] = [
                 &shared_install_state.borrow().is_map_installed(file.id()),
                 file.id(),
-                file.title(),
+                &title,
                 file.author(),
                 file.date(),
                 rating_image,
@@ -1,37 +1,19 @@
+use crate::configuration::DEFAULT_ENGINE_NAME;
 use crate::gui_data::GuiData;
+use crate::http;
 use crate::image_loader::ImageLoader;
 use gdk_pixbuf::Pixbuf;
-use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
+use glib::MainContext;
 use gtk::prelude::*;
 use log::*;
-use std::sync::atomic::{AtomicU8, Ordering};
-use std::thread;
-
-static THREAD_COUNTER: AtomicU8 = AtomicU8::new(0);
+use std::fs::remove_file;
 
 pub fn connect_selection_change(gui_data: &GuiData, tree_view: &gtk::TreeView) {
     let detail_pane = gui_data.detail_pane.clone();
     let shared_install_state = gui_data.shared_install_state.clone();
     let shared_files_state = gui_data.shared_files_state.clone();
     let shared_config_state = gui_data.shared_config_state.clone();
-    let (sender, receiver): (Sender<ImageLoader>, Receiver<ImageLoader>) =
-        MainContext::channel(PRIORITY_DEFAULT);
     let rec_tree_view = tree_view.clone();
-    let rec_detail_pane = detail_pane.clone();
-    receiver.attach(None, move |image_loader| {
-        let pixbuf = Pixbuf::from_file_at_size(image_loader.path(), 200, 200).unwrap();
-        match rec_tree_view.get_selection().get_selected() {
-            Some((model, iter)) => {
-                let current_path_string = model.get_string_from_iter(&iter).unwrap().to_string();
-                if &current_path_string == image_loader.path_string() {
-                    rec_detail_pane.update_image(pixbuf);
-                }
-            }
-            None => (),
-        };
-        THREAD_COUNTER.fetch_sub(1, Ordering::Relaxed);
-        Continue(true)
-    });
     tree_view
         .get_selection()
         .connect_changed(move |sel| match sel.get_selected() {
@@ -50,23 +32,71 @@ pub fn connect_selection_change(gui_data: &GuiData, tree_view: &gtk::TreeView) {
                 let is_local = shared_install_state.borrow().is_map_installed(&id_string);
                 let is_offline = *shared_config_state.borrow().is_offline();
                 let image_dir = shared_config_state.borrow().image_cache_dir().clone();
+                let not_found_image = shared_config_state
+                    .borrow()
+                    .image_resources_dir()
+                    .join("not_found.png");
+                let mirrors = shared_config_state.borrow().mirrors().clone();
                 let date_format = shared_config_state.borrow().get_date_format();
                 detail_pane.update(&file, is_local, is_offline, date_format);
-                let sender = sender.clone();
-                thread::Builder::new()
-                    .name(format!("select-{}", THREAD_COUNTER.load(Ordering::Relaxed)))
-                    .spawn(move || {
-                        THREAD_COUNTER.fetch_add(1, Ordering::Relaxed);
-                        let mut image_loader = ImageLoader::new(id_string, path_string);
-                        image_loader.load_map_image(is_offline, image_dir);
-                        match sender.send(image_loader) {
-                            Ok(_) => (),
-                            Err(e) => {
-                                error!("{}", e);
+                let engine_names: Vec<String> = shared_config_state
+                    .borrow()
+                    .engine_profiles()
+                    .iter()
+                    .map(|engine| engine.name().to_owned())
+                    .collect();
+                let map_engine = match shared_install_state.borrow().get_map(&id_string) {
+                    Some(map_pack) if !map_pack.engine().is_empty() => map_pack.engine().clone(),
+                    _ => DEFAULT_ENGINE_NAME.to_string(),
+                };
+                detail_pane.set_engines(&engine_names, &map_engine, is_local);
+                let fallback_image = not_found_image.clone();
+                // the fetch runs on the network runtime, only showing the result comes back here
+                let fetch = http::runtime().spawn(async move {
+                    let mut image_loader = ImageLoader::new(id_string, path_string)
+                        .with_not_found_image(not_found_image);
+                    image_loader
+                        .fetch_map_image(is_offline, image_dir, &mirrors)
+                        .await;
+                    image_loader
+                });
+                let rec_tree_view = rec_tree_view.clone();
+                let rec_detail_pane = detail_pane.clone();
+                MainContext::default().spawn_local(async move {
+                    let image_loader = match fetch.await {
+                        Ok(image_loader) => image_loader,
+                        Err(e) => {
+                            error!("Image fetch failed: {}", e);
+                            return;
+                        }
+                    };
+                    // a partial download or a bad file from a mirror gets the placeholder too
+                    let pixbuf = match Pixbuf::from_file_at_size(image_loader.path(), 200, 200) {
+                        Ok(pixbuf) => pixbuf,
+                        Err(e) => {
+                            warn!("Couldn't load image {:?}: {}", image_loader.path(), e);
+                            // so the next look fetches it again instead of reusing the bad copy
+                            if image_loader.path() != &fallback_image {
+                                let _ = remove_file(image_loader.path());
                             }
+                            match Pixbuf::from_file_at_size(&fallback_image, 200, 200) {
+                                Ok(pixbuf) => pixbuf,
+                                Err(e) => {
+                                    error!("Couldn't load image {:?}: {}", fallback_image, e);
+                                    return;
+                                }
+                            }
+                        }
+                    };
+                    // the user may have moved on while this one was downloading
+                    if let Some((model, iter)) = rec_tree_view.get_selection().get_selected() {
+                        let current_path_string =
+                            model.get_string_from_iter(&iter).unwrap().to_string();
+                        if &current_path_string == image_loader.path_string() {
+                            rec_detail_pane.update_image(pixbuf);
                         }
-                    })
-                    .expect("Failed to spawn select thread");
+                    }
+                });
             }
             None => {
                 trace!("Clearing selected");
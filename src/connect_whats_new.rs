@@ -0,0 +1,60 @@
+use crate::connect_search_event::refilter_list;
+use crate::gui_data::GuiData;
+use crate::whats_new::find_changes;
+use gtk::prelude::*;
+use log::*;
+
+pub fn connect_whats_new(gui_data: &GuiData) {
+    trace!("Initializing what's new buttons");
+    let btn_whats_new = gui_data.filter_bar.btn_whats_new.clone();
+    let toggle_gui_data = gui_data.clone();
+    btn_whats_new.connect_toggled(move |me| {
+        info!("What's new toggled to {}", me.get_active());
+        let gui_data = &toggle_gui_data;
+        gui_data
+            .filter_bar
+            .btn_mark_seen
+            .set_sensitive(me.get_active());
+        refilter_list(gui_data);
+    });
+
+    let btn_mark_seen = gui_data.filter_bar.btn_mark_seen.clone();
+    let seen_gui_data = gui_data.clone();
+    btn_mark_seen.connect_clicked(move |_| {
+        info!("Marking every map as seen");
+        let gui_data = &seen_gui_data;
+        let config_dir = gui_data.shared_config_state.borrow().config_dir().clone();
+        {
+            let mut seen_maps = gui_data.shared_seen_state.borrow_mut();
+            seen_maps.mark_seen(&gui_data.shared_files_state.borrow());
+            seen_maps.write_to_file(config_dir);
+        }
+        gui_data.filter_bar.btn_whats_new.set_active(false);
+        update_whats_new(gui_data);
+    });
+}
+
+/// Brings the what's new count up to date after the database was (re)loaded.
+pub fn update_whats_new(gui_data: &GuiData) {
+    let config_dir = gui_data.shared_config_state.borrow().config_dir().clone();
+    // nothing has been seen yet on a fresh config, so start from what's there now
+    if gui_data.shared_seen_state.borrow().maps().is_empty() {
+        debug!("No seen maps yet, taking the current database as seen");
+        let mut seen_maps = gui_data.shared_seen_state.borrow_mut();
+        seen_maps.mark_seen(&gui_data.shared_files_state.borrow());
+        seen_maps.write_to_file(config_dir);
+    }
+    let count = find_changes(
+        &gui_data.shared_files_state.borrow(),
+        &gui_data.shared_seen_state.borrow(),
+    )
+    .len();
+    debug!("{} maps are new or changed", count);
+    let config = gui_data.shared_config_state.borrow();
+    gui_data
+        .filter_bar
+        .set_whats_new_count(count, config.current_locale());
+    if gui_data.filter_bar.btn_whats_new.get_active() {
+        refilter_list(gui_data);
+    }
+}
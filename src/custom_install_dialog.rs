@@ -0,0 +1,83 @@
+use crate::initializable::Initializable;
+use crate::locales::Locale;
+use gtk::prelude::*;
+use gtk::{Builder, Button, Dialog, Entry, Label};
+
+#[derive(Clone)]
+pub struct CustomInstallDialog {
+    pub dlg_custom_install: Dialog,
+    pub lbl_custom_source: Label,
+    pub lbl_custom_id: Label,
+    pub lbl_custom_title: Label,
+    pub ent_custom_source: Entry,
+    pub ent_custom_id: Entry,
+    pub ent_custom_title: Entry,
+    pub btn_custom_install_ok: Button,
+    pub btn_custom_install_cancel: Button,
+}
+
+impl CustomInstallDialog {
+    pub fn create_from_builder(builder: &Builder) -> Self {
+        let dlg_custom_install: Dialog = builder
+            .get_object("dlg_custom_install")
+            .expect("Failed to get dlg_custom_install");
+        let lbl_custom_source: Label = builder
+            .get_object("lbl_custom_source")
+            .expect("Failed to get lbl_custom_source");
+        let lbl_custom_id: Label = builder
+            .get_object("lbl_custom_id")
+            .expect("Failed to get lbl_custom_id");
+        let lbl_custom_title: Label = builder
+            .get_object("lbl_custom_title")
+            .expect("Failed to get lbl_custom_title");
+        let ent_custom_source: Entry = builder
+            .get_object("ent_custom_source")
+            .expect("Failed to get ent_custom_source");
+        let ent_custom_id: Entry = builder
+            .get_object("ent_custom_id")
+            .expect("Failed to get ent_custom_id");
+        let ent_custom_title: Entry = builder
+            .get_object("ent_custom_title")
+            .expect("Failed to get ent_custom_title");
+        let btn_custom_install_ok: Button = builder
+            .get_object("btn_custom_install_ok")
+            .expect("Failed to get btn_custom_install_ok");
+        let btn_custom_install_cancel: Button = builder
+            .get_object("btn_custom_install_cancel")
+            .expect("Failed to get btn_custom_install_cancel");
+        Self {
+            dlg_custom_install,
+            lbl_custom_source,
+            lbl_custom_id,
+            lbl_custom_title,
+            ent_custom_source,
+            ent_custom_id,
+            ent_custom_title,
+            btn_custom_install_ok,
+            btn_custom_install_cancel,
+        }
+    }
+
+    pub fn show(&self, source: &str, map_id: &str) {
+        self.ent_custom_source.set_text(source);
+        self.ent_custom_id.set_text(map_id);
+        self.ent_custom_title.set_text("");
+        self.dlg_custom_install.show_all();
+    }
+}
+
+impl Initializable for CustomInstallDialog {
+    fn init_text(&self, locale: &Locale) {
+        self.dlg_custom_install
+            .set_title(locale.custom_dialog_title());
+        self.lbl_custom_source
+            .set_text(locale.custom_dialog_source_text());
+        self.lbl_custom_id.set_text(locale.custom_dialog_id_text());
+        self.lbl_custom_title
+            .set_text(locale.custom_dialog_title_text());
+        self.btn_custom_install_ok
+            .set_label(locale.universal_ok_button());
+        self.btn_custom_install_cancel
+            .set_label(locale.universal_cancel_button());
+    }
+}
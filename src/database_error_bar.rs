@@ -0,0 +1,61 @@
+use crate::initializable::Initializable;
+use crate::locales::Locale;
+use crate::quake_file::DatabaseError;
+use gtk::prelude::*;
+use gtk::{Builder, Button, InfoBar, Label, MessageType};
+
+#[derive(Clone)]
+pub struct DatabaseErrorBar {
+    pub info_database_error: InfoBar,
+    pub lbl_database_error: Label,
+    pub btn_database_retry: Button,
+}
+
+impl DatabaseErrorBar {
+    pub fn create_from_builder(builder: &Builder) -> Self {
+        let info_database_error: InfoBar = builder
+            .get_object("info_database_error")
+            .expect("Failed to get info_database_error");
+        let lbl_database_error: Label = builder
+            .get_object("lbl_database_error")
+            .expect("Failed to get lbl_database_error");
+        let btn_database_retry: Button = builder
+            .get_object("btn_database_retry")
+            .expect("Failed to get btn_database_retry");
+        Self {
+            info_database_error,
+            lbl_database_error,
+            btn_database_retry,
+        }
+    }
+
+    pub fn show_error(&self, error: &DatabaseError, is_fallback: bool, locale: &Locale) {
+        let reason = match error {
+            DatabaseError::Offline => locale.database_offline_text().to_owned(),
+            DatabaseError::Unavailable(e) => {
+                format!("{} ({})", locale.database_unavailable_text(), e)
+            }
+        };
+        let (text, message_type) = match is_fallback {
+            true => (
+                format!("{} {}", reason, locale.database_fallback_text()),
+                MessageType::Warning,
+            ),
+            false => (reason, MessageType::Error),
+        };
+        self.lbl_database_error.set_text(&text);
+        self.info_database_error.set_message_type(message_type);
+        self.info_database_error.show_all();
+    }
+
+    pub fn hide(&self) {
+        self.info_database_error.hide();
+    }
+}
+
+impl Initializable for DatabaseErrorBar {
+    fn init_text(&self, locale: &Locale) {
+        self.btn_database_retry
+            .set_label(locale.database_retry_button());
+    }
+}
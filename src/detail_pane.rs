@@ -4,10 +4,8 @@ use crate::quake_file::QuakeFile;
 use chrono::NaiveDate;
 use gdk_pixbuf::{Pixbuf, PixbufAnimation};
 use gtk::prelude::*;
-use gtk::{Builder, Button, ComboBoxText, Image, Label, ProgressBar, ScrolledWindow};
+use gtk::{Builder, Button, ComboBoxText, Image, Label, ScrolledWindow};
 use log::*;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 #[derive(Clone)]
 pub struct DetailPane {
@@ -17,12 +15,12 @@ pub struct DetailPane {
     pub btn_install: Button,
     pub btn_uninstall: Button,
     pub btn_play: Button,
+    pub btn_verify: Button,
     pub lbl_date: Label,
     pub lbl_size: Label,
     pub sw_details: ScrolledWindow,
-    pub box_progress: gtk::Box,
     pub dropdown: ComboBoxText,
-    progress_bars: Rc<RefCell<Vec<ProgressBar>>>,
+    pub engine_dropdown: ComboBoxText,
 }
 
 impl DetailPane {
@@ -46,6 +44,9 @@ impl DetailPane {
         let btn_play: Button = builder
             .get_object("btn_play")
             .expect("Failed to get btn_play");
+        let btn_verify: Button = builder
+            .get_object("btn_verify")
+            .expect("Failed to get btn_verify");
         let sw_details: ScrolledWindow = builder
             .get_object("sw_details")
             .expect("Failed to get sw_detail");
@@ -55,12 +56,12 @@ impl DetailPane {
         let lbl_size: Label = builder
             .get_object("lbl_size")
             .expect("Failed to get lbl_size");
-        let box_progress: gtk::Box = builder
-            .get_object("box_progress")
-            .expect("Failed to get box_progress");
         let dropdown: ComboBoxText = builder
             .get_object("start_combo_box")
             .expect("Failed to get start_combo_box");
+        let engine_dropdown: ComboBoxText = builder
+            .get_object("engine_combo_box")
+            .expect("Failed to get engine_combo_box");
         Self {
             lbl_title,
             lbl_description,
@@ -68,12 +69,12 @@ impl DetailPane {
             btn_play,
             btn_install,
             btn_uninstall,
+            btn_verify,
             sw_details,
             lbl_date,
             lbl_size,
-            box_progress,
             dropdown,
-            progress_bars: Rc::new(RefCell::new(vec![])),
+            engine_dropdown,
         }
     }
 
@@ -89,6 +90,7 @@ impl DetailPane {
         self.btn_install.set_sensitive(!is_local && !is_offline);
         self.btn_uninstall.set_sensitive(is_local);
         self.btn_play.set_sensitive(is_local);
+        self.btn_verify.set_sensitive(is_local);
 
         self.img_current_map.set_visible(false); // hide image until it loads
         let start_maps = file.tech_info().start_map();
@@ -120,8 +122,23 @@ impl DetailPane {
         self.btn_install.set_sensitive(false);
         self.btn_uninstall.set_sensitive(false);
         self.btn_play.set_sensitive(false);
+        self.btn_verify.set_sensitive(false);
         self.img_current_map.set_visible(false);
         self.dropdown.remove_all();
+        self.engine_dropdown.remove_all();
+    }
+
+    /// Fills the engine choice with `names`, `selected` being the one the map is played with.
+    pub fn set_engines(&self, names: &[String], selected: &str, is_local: bool) {
+        self.engine_dropdown.remove_all();
+        for name in names {
+            self.engine_dropdown.append(Some(name), name);
+        }
+        if !self.engine_dropdown.set_active_id(Some(selected)) {
+            self.engine_dropdown.set_active(Some(0));
+        }
+        self.engine_dropdown
+            .set_sensitive(is_local && names.len() > 1);
     }
 
     pub fn set_spinner(&self, anim: PixbufAnimation) {
@@ -133,24 +150,6 @@ impl DetailPane {
         self.img_current_map.set_from_pixbuf(Some(&pixbuf));
         self.img_current_map.set_visible(true);
     }
-
-    pub fn add_progress_bar(&mut self, map_id: &String) {
-        let progress_bar = ProgressBar::new();
-        progress_bar.set_text(Some(map_id));
-        progress_bar.set_show_text(true);
-        self.box_progress.add(&progress_bar);
-        self.progress_bars.borrow_mut().push(progress_bar);
-        self.box_progress.show_all();
-    }
-
-    pub fn update_progress_bar(&self, map_id: &String, fraction: f64) {
-        let bars = self.progress_bars.borrow();
-        let bar = bars
-            .iter()
-            .find(|bar| map_id.contains(bar.get_text().unwrap().as_str()))
-            .unwrap();
-        bar.set_fraction(fraction);
-    }
 }
 
 impl Initializable for DetailPane {
@@ -158,6 +157,9 @@ impl Initializable for DetailPane {
         self.btn_install.set_label(locale.install_button_text());
         self.btn_uninstall.set_label(locale.uninstall_button_text());
         self.btn_play.set_label(locale.play_button_text());
+        self.btn_verify.set_label(locale.verify_button_text());
+        self.engine_dropdown
+            .set_tooltip_text(Some(locale.engine_choice_tooltip()));
     }
 }
 
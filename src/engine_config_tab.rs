@@ -1,8 +1,10 @@
 use crate::configuration::Configuration;
 use crate::initializable::Initializable;
+use crate::install_detection::DetectedInstall;
 use crate::locales::Locale;
+use crate::quake_paths::MissionPack;
 use gtk::prelude::*;
-use gtk::{Builder, Button, CheckButton, Entry, Label};
+use gtk::{Builder, Button, CheckButton, ComboBoxText, Entry, Label, SpinButton};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -11,6 +13,10 @@ pub struct EngineConfigTab {
     pub btn_quake_dir: Button,
     pub btn_quake_exe: Button,
     pub btn_download_dir: Button,
+    pub btn_use_detected: Button,
+    pub btn_detect_installs: Button,
+
+    pub dropdown_detected_installs: ComboBoxText,
 
     pub ent_command_opts: Entry,
     pub ent_quake_dir: Entry,
@@ -18,14 +24,23 @@ pub struct EngineConfigTab {
     pub ent_download_dir: Entry,
 
     pub lbl_config_dialog_header: Label,
+    pub lbl_config_dialog_detected: Label,
     pub lbl_config_dialog_command_line: Label,
     pub lbl_config_dialog_quake_dir: Label,
     pub lbl_config_dialog_quake_exe: Label,
     pub lbl_config_dialog_download_dir: Label,
     pub lbl_config_dialog_exp_packs: Label,
+    pub lbl_config_dialog_max_downloads: Label,
+    pub lbl_config_dialog_database_max_age: Label,
 
     pub chk_rogue: CheckButton,
     pub chk_hipnotic: CheckButton,
+
+    pub spin_max_downloads: SpinButton,
+    pub spin_database_max_age: SpinButton,
+
+    // what the last search found, in the same order as the dropdown
+    pub detected_installs: Rc<RefCell<Vec<DetectedInstall>>>,
 }
 
 impl EngineConfigTab {
@@ -39,6 +54,15 @@ impl EngineConfigTab {
         let btn_quake_dir: Button = builder
             .get_object("dlg_quake_dir_btn_select")
             .expect("Failed to get btn_quake_dir");
+        let btn_use_detected: Button = builder
+            .get_object("btn_use_detected")
+            .expect("Failed to get btn_use_detected");
+        let btn_detect_installs: Button = builder
+            .get_object("btn_detect_installs")
+            .expect("Failed to get btn_detect_installs");
+        let dropdown_detected_installs: ComboBoxText = builder
+            .get_object("dropdown_detected_installs")
+            .expect("Failed to get dropdown_detected_installs");
         let ent_command_opts: Entry = builder
             .get_object("ent_command_opts")
             .expect("Failed to get ent_command_opts");
@@ -54,6 +78,9 @@ impl EngineConfigTab {
         let lbl_config_dialog_header: Label = builder
             .get_object("lbl_config_dialog_header")
             .expect("Failed to get lbl_config_dialog_header");
+        let lbl_config_dialog_detected: Label = builder
+            .get_object("lbl_config_dialog_detected")
+            .expect("Failed to get lbl_config_dialog_detected");
         let lbl_config_dialog_quake_dir: Label = builder
             .get_object("lbl_config_dialog_quake_dir")
             .expect("Failed to get lbl_config_dialog_quake_dir");
@@ -69,28 +96,49 @@ impl EngineConfigTab {
         let lbl_config_dialog_exp_packs: Label = builder
             .get_object("lbl_config_dialog_exp_packs")
             .expect("Failed to get lbl_config_dialog_exp_packs");
+        let lbl_config_dialog_max_downloads: Label = builder
+            .get_object("lbl_config_dialog_max_downloads")
+            .expect("Failed to get lbl_config_dialog_max_downloads");
+        let lbl_config_dialog_database_max_age: Label = builder
+            .get_object("lbl_config_dialog_database_max_age")
+            .expect("Failed to get lbl_config_dialog_database_max_age");
         let chk_rogue: CheckButton = builder
             .get_object("dlg_btn_rogue")
             .expect("Failed to get dlg_btn_rogue");
         let chk_hipnotic: CheckButton = builder
             .get_object("dlg_btn_hipnotic")
             .expect("Failed to get dlg_btn_hipnotic");
+        let spin_max_downloads: SpinButton = builder
+            .get_object("spin_max_downloads")
+            .expect("Failed to get spin_max_downloads");
+        let spin_database_max_age: SpinButton = builder
+            .get_object("spin_database_max_age")
+            .expect("Failed to get spin_database_max_age");
         Self {
             btn_quake_dir,
             btn_quake_exe,
             btn_download_dir,
+            btn_use_detected,
+            btn_detect_installs,
+            dropdown_detected_installs,
             ent_command_opts,
             ent_quake_dir,
             ent_quake_exe,
             ent_download_dir,
             lbl_config_dialog_header,
+            lbl_config_dialog_detected,
             lbl_config_dialog_quake_dir,
             lbl_config_dialog_quake_exe,
             lbl_config_dialog_command_line,
             lbl_config_dialog_download_dir,
             lbl_config_dialog_exp_packs,
+            lbl_config_dialog_max_downloads,
+            lbl_config_dialog_database_max_age,
             chk_rogue,
             chk_hipnotic,
+            spin_max_downloads,
+            spin_database_max_age,
+            detected_installs: Rc::new(RefCell::new(vec![])),
         }
     }
 
@@ -99,6 +147,64 @@ impl EngineConfigTab {
         self.ent_quake_dir.set_text(borrow.quake_dir());
         self.ent_download_dir.set_text(borrow.download_dir());
         self.ent_quake_exe.set_text(borrow.quake_exe());
+        self.ent_command_opts.set_text(borrow.launch_options());
+        self.spin_max_downloads
+            .set_value(*borrow.max_concurrent_downloads() as f64);
+        self.spin_database_max_age
+            .set_value(*borrow.database_max_age_hours() as f64);
+        self.show_mission_packs(borrow.quake_dir());
+    }
+
+    // the checkboxes only show what's there, the pak files decide
+    pub fn show_mission_packs(&self, quake_dir: &str) {
+        self.chk_hipnotic
+            .set_active(MissionPack::Hipnotic.is_installed(quake_dir));
+        self.chk_rogue
+            .set_active(MissionPack::Rogue.is_installed(quake_dir));
+    }
+
+    pub fn set_detected(&self, installs: Vec<DetectedInstall>, locale: &Locale) {
+        self.dropdown_detected_installs.remove_all();
+        for install in installs.iter() {
+            let engine = match install.quake_exe() {
+                Some(exe) => exe.to_string_lossy().to_string(),
+                None => locale.config_dialog_detected_no_engine_text().to_owned(),
+            };
+            let text = format!(
+                "{} ({}) - {}",
+                install.quake_dir().display(),
+                install.source(),
+                engine
+            );
+            self.dropdown_detected_installs.append_text(&text);
+        }
+        if installs.is_empty() {
+            self.dropdown_detected_installs
+                .append_text(locale.config_dialog_detected_none_text());
+        }
+        self.dropdown_detected_installs.set_active(Some(0));
+        self.btn_use_detected.set_sensitive(!installs.is_empty());
+        self.btn_detect_installs.set_sensitive(true);
+        self.detected_installs.replace(installs);
+    }
+
+    /// Fills in the directory and executable from the picked search result.
+    pub fn use_detected(&self) {
+        let index = match self.dropdown_detected_installs.get_active() {
+            Some(index) => index as usize,
+            None => return,
+        };
+        let installs = self.detected_installs.borrow();
+        let install = match installs.get(index) {
+            Some(install) => install,
+            None => return,
+        };
+        self.ent_quake_dir
+            .set_text(&install.quake_dir().to_string_lossy());
+        // keep whatever was picked already when the search found no engine
+        if let Some(exe) = install.quake_exe() {
+            self.ent_quake_exe.set_text(&exe.to_string_lossy());
+        }
     }
 }
 
@@ -112,6 +218,12 @@ impl Initializable for EngineConfigTab {
             .set_label(locale.config_dialog_select_button_text());
         self.lbl_config_dialog_header
             .set_label(locale.config_dialog_header_text());
+        self.lbl_config_dialog_detected
+            .set_label(locale.config_dialog_detected_text());
+        self.btn_use_detected
+            .set_label(locale.config_dialog_use_detected_button());
+        self.btn_detect_installs
+            .set_label(locale.config_dialog_detect_button());
         self.lbl_config_dialog_command_line
             .set_label(locale.config_dialog_command_line_text());
         self.lbl_config_dialog_quake_dir
@@ -126,5 +238,13 @@ impl Initializable for EngineConfigTab {
             .set_label(locale.config_dialog_expansion_hip_text());
         self.chk_rogue
             .set_label(locale.config_dialog_expansion_rogue_text());
+        self.chk_hipnotic
+            .set_tooltip_text(Some(locale.config_dialog_expansion_detected_tooltip()));
+        self.chk_rogue
+            .set_tooltip_text(Some(locale.config_dialog_expansion_detected_tooltip()));
+        self.lbl_config_dialog_max_downloads
+            .set_label(locale.config_dialog_max_downloads_text());
+        self.lbl_config_dialog_database_max_age
+            .set_label(locale.config_dialog_database_max_age_text());
     }
 }
@@ -0,0 +1,199 @@
+use crate::configuration::{Configuration, EngineProfile};
+use crate::initializable::Initializable;
+use crate::locales::Locale;
+use gtk::prelude::*;
+use gtk::{Builder, Button, CheckButton, ComboBoxText, Entry, Label};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct EnginesConfigTab {
+    pub tab_engines: Label,
+
+    pub btn_engine_exe: Button,
+    pub btn_engine_save: Button,
+    pub btn_engine_remove: Button,
+
+    pub chk_engine_basedir: CheckButton,
+
+    pub dropdown_engine_profiles: ComboBoxText,
+
+    pub ent_engine_name: Entry,
+    pub ent_engine_exe: Entry,
+    pub ent_engine_dir: Entry,
+    pub ent_engine_options: Entry,
+    pub ent_engine_game_flag: Entry,
+
+    pub lbl_engines_title: Label,
+    pub lbl_engine_name: Label,
+    pub lbl_engine_exe: Label,
+    pub lbl_engine_dir: Label,
+    pub lbl_engine_options: Label,
+    pub lbl_engine_game_flag: Label,
+
+    // edited here, only put in the config when the dialog is confirmed
+    pub profiles: Rc<RefCell<Vec<EngineProfile>>>,
+}
+
+impl EnginesConfigTab {
+    pub fn create_from_builder(builder: &Builder) -> Self {
+        let tab_engines: Label = builder
+            .get_object("tab_engines")
+            .expect("Failed to get tab_engines");
+        let btn_engine_exe: Button = builder
+            .get_object("btn_engine_exe")
+            .expect("Failed to get btn_engine_exe");
+        let btn_engine_save: Button = builder
+            .get_object("btn_engine_save")
+            .expect("Failed to get btn_engine_save");
+        let btn_engine_remove: Button = builder
+            .get_object("btn_engine_remove")
+            .expect("Failed to get btn_engine_remove");
+        let chk_engine_basedir: CheckButton = builder
+            .get_object("chk_engine_basedir")
+            .expect("Failed to get chk_engine_basedir");
+        let dropdown_engine_profiles: ComboBoxText = builder
+            .get_object("dropdown_engine_profiles")
+            .expect("Failed to get dropdown_engine_profiles");
+        let ent_engine_name: Entry = builder
+            .get_object("ent_engine_name")
+            .expect("Failed to get ent_engine_name");
+        let ent_engine_exe: Entry = builder
+            .get_object("ent_engine_exe")
+            .expect("Failed to get ent_engine_exe");
+        let ent_engine_dir: Entry = builder
+            .get_object("ent_engine_dir")
+            .expect("Failed to get ent_engine_dir");
+        let ent_engine_options: Entry = builder
+            .get_object("ent_engine_options")
+            .expect("Failed to get ent_engine_options");
+        let ent_engine_game_flag: Entry = builder
+            .get_object("ent_engine_game_flag")
+            .expect("Failed to get ent_engine_game_flag");
+        let lbl_engines_title: Label = builder
+            .get_object("lbl_engines_title")
+            .expect("Failed to get lbl_engines_title");
+        let lbl_engine_name: Label = builder
+            .get_object("lbl_engine_name")
+            .expect("Failed to get lbl_engine_name");
+        let lbl_engine_exe: Label = builder
+            .get_object("lbl_engine_exe")
+            .expect("Failed to get lbl_engine_exe");
+        let lbl_engine_dir: Label = builder
+            .get_object("lbl_engine_dir")
+            .expect("Failed to get lbl_engine_dir");
+        let lbl_engine_options: Label = builder
+            .get_object("lbl_engine_options")
+            .expect("Failed to get lbl_engine_options");
+        let lbl_engine_game_flag: Label = builder
+            .get_object("lbl_engine_game_flag")
+            .expect("Failed to get lbl_engine_game_flag");
+        Self {
+            tab_engines,
+            btn_engine_exe,
+            btn_engine_save,
+            btn_engine_remove,
+            chk_engine_basedir,
+            dropdown_engine_profiles,
+            ent_engine_name,
+            ent_engine_exe,
+            ent_engine_dir,
+            ent_engine_options,
+            ent_engine_game_flag,
+            lbl_engines_title,
+            lbl_engine_name,
+            lbl_engine_exe,
+            lbl_engine_dir,
+            lbl_engine_options,
+            lbl_engine_game_flag,
+            profiles: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    pub fn show(&self, shared_config: Rc<RefCell<Configuration>>) {
+        self.profiles
+            .replace(shared_config.borrow().engines().clone());
+        self.refresh_list(None);
+    }
+
+    /// Lists the profiles again, selecting `selected` or starting a new one when that's None.
+    pub fn refresh_list(&self, selected: Option<&str>) {
+        self.dropdown_engine_profiles.remove_all();
+        for profile in self.profiles.borrow().iter() {
+            self.dropdown_engine_profiles
+                .append(Some(profile.name()), profile.name());
+        }
+        match selected {
+            Some(name) => {
+                self.dropdown_engine_profiles.set_active_id(Some(name));
+            }
+            None => self.fill_fields(None),
+        }
+    }
+
+    pub fn fill_fields(&self, profile: Option<&EngineProfile>) {
+        let empty = EngineProfile::new("", "");
+        let profile = profile.unwrap_or(&empty);
+        self.ent_engine_name.set_text(profile.name());
+        self.ent_engine_exe.set_text(profile.quake_exe());
+        self.ent_engine_dir.set_text(profile.quake_dir());
+        self.ent_engine_options.set_text(profile.launch_options());
+        self.ent_engine_game_flag.set_text(profile.game_flag());
+        self.chk_engine_basedir.set_active(*profile.uses_basedir());
+        self.btn_engine_remove
+            .set_sensitive(!profile.name().is_empty());
+    }
+
+    pub fn profile_from_fields(&self) -> EngineProfile {
+        let name = self.ent_engine_name.get_buffer().get_text();
+        let quake_exe = self.ent_engine_exe.get_buffer().get_text();
+        let mut profile = EngineProfile::new(name.trim(), quake_exe.trim());
+        profile.set_quake_dir(
+            self.ent_engine_dir
+                .get_buffer()
+                .get_text()
+                .trim()
+                .to_string(),
+        );
+        profile.set_launch_options(
+            self.ent_engine_options
+                .get_buffer()
+                .get_text()
+                .trim()
+                .to_string(),
+        );
+        let game_flag = self.ent_engine_game_flag.get_buffer().get_text();
+        if !game_flag.trim().is_empty() {
+            profile.set_game_flag(game_flag.trim().to_string());
+        }
+        profile.set_uses_basedir(self.chk_engine_basedir.get_active());
+        profile
+    }
+}
+
+impl Initializable for EnginesConfigTab {
+    fn init_text(&self, locale: &Locale) {
+        self.tab_engines
+            .set_label(locale.config_dialog_engines_tab());
+        self.lbl_engines_title
+            .set_label(locale.config_dialog_engines_title());
+        self.lbl_engine_name
+            .set_label(locale.config_dialog_engine_name_text());
+        self.lbl_engine_exe
+            .set_label(locale.config_dialog_quake_exe_text());
+        self.lbl_engine_dir
+            .set_label(locale.config_dialog_engine_dir_text());
+        self.lbl_engine_options
+            .set_label(locale.config_dialog_engine_options_text());
+        self.lbl_engine_game_flag
+            .set_label(locale.config_dialog_engine_game_flag_text());
+        self.chk_engine_basedir
+            .set_label(locale.config_dialog_engine_basedir_text());
+        self.btn_engine_exe
+            .set_label(locale.config_dialog_select_button_text());
+        self.btn_engine_save
+            .set_label(locale.config_dialog_engine_save_button());
+        self.btn_engine_remove
+            .set_label(locale.config_dialog_engine_remove_button());
+    }
+}
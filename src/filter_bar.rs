@@ -1,16 +1,26 @@
 use crate::initializable::Initializable;
 use crate::locales::Locale;
 use gtk::prelude::*;
-use gtk::{Builder, Button, Entry};
+use gtk::{Builder, Button, Entry, ToggleButton};
 
 #[derive(Clone)]
 pub struct FilterBar {
     pub entry_filter_text: Entry,
     pub btn_clear_filter: Button,
     pub btn_install_random: Button,
+    pub btn_whats_new: ToggleButton,
+    pub btn_mark_seen: Button,
 }
 
 impl FilterBar {
+    pub fn set_whats_new_count(&self, count: usize, locale: &Locale) {
+        let label = match count {
+            0 => locale.whats_new_button().to_owned(),
+            _ => format!("{} ({})", locale.whats_new_button(), count),
+        };
+        self.btn_whats_new.set_label(&label);
+    }
+
     pub fn create_from_builder(builder: &Builder) -> Self {
         let entry_filter_text: Entry = builder
             .get_object("entry_filter_text")
@@ -21,10 +31,18 @@ impl FilterBar {
         let btn_install_random: Button = builder
             .get_object("btn_install_random")
             .expect("Failed to get btn_install_random");
+        let btn_whats_new: ToggleButton = builder
+            .get_object("btn_whats_new")
+            .expect("Failed to get btn_whats_new");
+        let btn_mark_seen: Button = builder
+            .get_object("btn_mark_seen")
+            .expect("Failed to get btn_mark_seen");
         Self {
             entry_filter_text,
             btn_clear_filter,
             btn_install_random,
+            btn_whats_new,
+            btn_mark_seen,
         }
     }
 }
@@ -37,5 +55,7 @@ impl Initializable for FilterBar {
             .set_label(locale.install_random_map_button());
         self.entry_filter_text
             .set_placeholder_text(Some(locale.filter_text_placeholder()));
+        self.btn_whats_new.set_label(locale.whats_new_button());
+        self.btn_mark_seen.set_label(locale.mark_seen_button());
     }
 }
@@ -1,13 +1,21 @@
 use crate::clear_cache_dialog::ClearCacheDialog;
 use crate::config_dialog::ConfigDialog;
-use crate::configuration::{Configuration, LocalMaps};
+use crate::configuration::{Configuration, LocalMaps, SeenMaps};
+use crate::custom_install_dialog::CustomInstallDialog;
+use crate::database_error_bar::DatabaseErrorBar;
 use crate::detail_pane::DetailPane;
+use crate::download_queue::DownloadQueue;
 use crate::filter_bar::FilterBar;
+use crate::game_player::RunningGame;
+use crate::http;
+use crate::launch_args::LaunchArgs;
+use crate::launch_dialog::LaunchDialog;
 use crate::list_view::ListView;
 use crate::main_menu::MainMenu;
 use crate::output_dialog::OutputDialog;
 use crate::progress_dialog::ProgressDialog;
 use crate::quake_file::QuakeFile;
+use crate::queue_panel::QueuePanel;
 use gdk_pixbuf::Pixbuf;
 use gtk::prelude::*;
 use gtk::{Builder, Window};
@@ -22,17 +30,27 @@ pub struct GuiData {
     pub window: Window,
     pub main_menu: MainMenu,
     pub filter_bar: FilterBar,
+    pub database_error_bar: DatabaseErrorBar,
     pub detail_pane: DetailPane,
     pub list_view: ListView,
     pub config_dialog: ConfigDialog,
     pub output_dialog: OutputDialog,
+    pub launch_dialog: LaunchDialog,
     pub clear_cache_dialog: ClearCacheDialog,
+    pub custom_install_dialog: CustomInstallDialog,
     pub progress_dialog: ProgressDialog,
+    pub queue_panel: QueuePanel,
 
     pub shared_install_state: Rc<RefCell<LocalMaps>>,
     pub shared_files_state: Rc<RefCell<Vec<QuakeFile>>>,
     pub shared_config_state: Rc<RefCell<Configuration>>,
     pub shared_images: Rc<RefCell<[gdk_pixbuf::Pixbuf; 6]>>,
+    pub shared_queue_state: Rc<RefCell<DownloadQueue>>,
+    pub shared_seen_state: Rc<RefCell<SeenMaps>>,
+    // the launch waiting on the user to confirm it
+    pub shared_launch_state: Rc<RefCell<Option<LaunchArgs>>>,
+    // the game started last, until it exits
+    pub shared_game_state: Rc<RefCell<Option<RunningGame>>>,
 }
 
 impl GuiData {
@@ -47,19 +65,29 @@ impl GuiData {
         window.set_icon(Some(&pixbuf));
         let main_menu = MainMenu::create_from_builder(&builder);
         let filter_bar = FilterBar::create_from_builder(&builder);
+        let database_error_bar = DatabaseErrorBar::create_from_builder(&builder);
         let detail_pane = DetailPane::create_from_builder(&builder);
         let list_view = ListView::create_from_builder(&builder);
         let config_dialog = ConfigDialog::create_from_builder(&builder);
         let output_dialog = OutputDialog::create_from_builder(&builder);
+        let launch_dialog = LaunchDialog::create_from_builder(&builder);
         let clear_cache_dialog = ClearCacheDialog::create_from_builder(&builder);
+        let custom_install_dialog = CustomInstallDialog::create_from_builder(&builder);
         let progress_dialog = ProgressDialog::create_from_builder(&builder);
+        let queue_panel = QueuePanel::create_from_builder(&builder);
         let configuration = Configuration::new();
+        http::configure(&configuration.network_settings());
         let app_title = configuration.current_locale().app_title().to_owned();
         let config_dir = configuration.config_dir().clone();
-        let shared_install_state = Rc::new(RefCell::new(LocalMaps::new(config_dir)));
+        let max_downloads = *configuration.max_concurrent_downloads() as usize;
+        let shared_install_state = Rc::new(RefCell::new(LocalMaps::new(config_dir.clone())));
+        let shared_seen_state = Rc::new(RefCell::new(SeenMaps::new(config_dir)));
+        let shared_launch_state = Rc::new(RefCell::new(None));
+        let shared_game_state = Rc::new(RefCell::new(None));
         let shared_files_state = Rc::new(RefCell::new(vec![]));
         let shared_config_state = Rc::new(RefCell::new(configuration));
         let shared_images = Rc::new(RefCell::new(init_shared_images()));
+        let shared_queue_state = Rc::new(RefCell::new(DownloadQueue::new(max_downloads)));
         window.set_title(&app_title);
         window.set_position(gtk::WindowPosition::CenterAlways);
         window.show_all();
@@ -77,16 +105,24 @@ impl GuiData {
             window,
             main_menu,
             filter_bar,
+            database_error_bar,
             detail_pane,
             list_view,
             config_dialog,
             output_dialog,
+            launch_dialog,
             clear_cache_dialog,
+            custom_install_dialog,
             progress_dialog,
+            queue_panel,
             shared_install_state,
             shared_files_state,
             shared_config_state,
             shared_images,
+            shared_queue_state,
+            shared_seen_state,
+            shared_launch_state,
+            shared_game_state,
         }
     }
 }
@@ -1,12 +1,15 @@
 use crate::connect_config_dialog;
+use crate::connect_custom_install;
 use crate::connect_detail_buttons;
 use crate::connect_menu_options;
 use crate::connect_output_dialog;
 use crate::connect_search_event;
 use crate::connect_selection_change;
+use crate::connect_whats_new;
 use crate::gui_data::GuiData;
 use crate::initializable::Initializable;
 use crate::list_view::populate_list_view;
+use gtk::prelude::*;
 
 pub fn initialize_gui(first_time_init: bool) {
     let gui_data = GuiData::new();
@@ -32,8 +35,11 @@ fn create_list_view(gui_data: &GuiData, first_time_init: bool) {
         let dialog = gui_data.config_dialog.clone();
         let config_state = gui_data.shared_config_state.clone();
         dialog.show(config_state);
+        // save them the browsing if Quake is somewhere we know to look
+        dialog.engine_config_tab.btn_detect_installs.clicked();
     }
     populate_list_view(gui_data);
+    connect_menu_options::refresh_database_if_stale(gui_data);
     connect_search_event::connect_search_event(&gui_data);
 }
 
@@ -44,6 +50,9 @@ fn initialize_config_dialog(gui_data: &GuiData) {
     connect_config_dialog::connect_cancel(gui_data);
     connect_config_dialog::connect_ok(gui_data);
     connect_config_dialog::connect_selects(gui_data);
+    connect_config_dialog::connect_engine_profiles(gui_data);
+    connect_config_dialog::connect_install_detection(gui_data);
+    connect_config_dialog::connect_mission_packs(gui_data);
 }
 
 fn initialize_detail_buttons(gui_data: &GuiData) {
@@ -52,12 +61,18 @@ fn initialize_detail_buttons(gui_data: &GuiData) {
     connect_detail_buttons::connect_install_map(gui_data);
     connect_detail_buttons::connect_uninstall_map(gui_data);
     connect_detail_buttons::connect_play_button(gui_data);
+    connect_detail_buttons::connect_launch_dialog(gui_data);
+    connect_detail_buttons::connect_engine_choice(gui_data);
+    connect_detail_buttons::connect_verify_button(gui_data);
 }
 
 fn initialize_output_dialog(gui_data: &GuiData) {
     let output_dialog = gui_data.output_dialog.clone();
     init_text(output_dialog, gui_data);
+    let launch_dialog = gui_data.launch_dialog.clone();
+    init_text(launch_dialog, gui_data);
     connect_output_dialog::connect_ok(gui_data);
+    connect_output_dialog::connect_kill_game(gui_data);
 }
 
 fn initialize_menu(gui_data: &GuiData) {
@@ -75,12 +90,25 @@ fn initialize_menu(gui_data: &GuiData) {
     connect_menu_options::connect_clear_cache_ok(gui_data);
     connect_menu_options::connect_clear_cache_cancel(gui_data);
     connect_menu_options::connect_clear_cache(gui_data);
+    connect_menu_options::connect_verify_cache(gui_data);
+    connect_menu_options::connect_verify_installs(gui_data);
+    connect_menu_options::connect_database_retry(gui_data);
+
+    let custom_install_dialog = gui_data.custom_install_dialog.clone();
+    init_text(custom_install_dialog, gui_data);
+    connect_custom_install::connect_install_from_file(gui_data);
+    connect_custom_install::connect_install_from_url(gui_data);
+    connect_custom_install::connect_custom_install_ok(gui_data);
+    connect_custom_install::connect_custom_install_cancel(gui_data);
 }
 
 fn initialize_filter_bar(gui_data: &GuiData) {
     let filter_bar = gui_data.filter_bar.clone();
     init_text(filter_bar, gui_data);
+    let database_error_bar = gui_data.database_error_bar.clone();
+    init_text(database_error_bar, gui_data);
     connect_detail_buttons::connect_install_random_map(gui_data);
+    connect_whats_new::connect_whats_new(gui_data);
 }
 
 fn init_text(element: impl Initializable, gui_data: &GuiData) {
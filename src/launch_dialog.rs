@@ -0,0 +1,79 @@
+use crate::initializable::Initializable;
+use crate::locales::Locale;
+use gtk::prelude::*;
+use gtk::{Builder, Button, Dialog, Entry, Label, TextView};
+
+#[derive(Clone)]
+pub struct LaunchDialog {
+    pub dlg_launch: Dialog,
+    pub btn_launch_ok: Button,
+    pub btn_launch_cancel: Button,
+    pub lbl_launch_command: Label,
+    pub lbl_launch_map_options: Label,
+    pub ent_launch_map_options: Entry,
+    pub txt_launch_command: TextView,
+}
+
+impl LaunchDialog {
+    pub fn create_from_builder(builder: &Builder) -> Self {
+        let dlg_launch: Dialog = builder
+            .get_object("dlg_launch")
+            .expect("Failed to get dlg_launch");
+        dlg_launch.connect_delete_event(move |me, _| me.hide_on_delete());
+        let btn_launch_ok: Button = builder
+            .get_object("btn_launch_ok")
+            .expect("Failed to get btn_launch_ok");
+        let btn_launch_cancel: Button = builder
+            .get_object("btn_launch_cancel")
+            .expect("Failed to get btn_launch_cancel");
+        let lbl_launch_command: Label = builder
+            .get_object("lbl_launch_command")
+            .expect("Failed to get lbl_launch_command");
+        let lbl_launch_map_options: Label = builder
+            .get_object("lbl_launch_map_options")
+            .expect("Failed to get lbl_launch_map_options");
+        let ent_launch_map_options: Entry = builder
+            .get_object("ent_launch_map_options")
+            .expect("Failed to get ent_launch_map_options");
+        let txt_launch_command: TextView = builder
+            .get_object("txt_launch_command")
+            .expect("Failed to get txt_launch_command");
+        Self {
+            dlg_launch,
+            btn_launch_ok,
+            btn_launch_cancel,
+            lbl_launch_command,
+            lbl_launch_map_options,
+            ent_launch_map_options,
+            txt_launch_command,
+        }
+    }
+
+    /// Shows the command that will run, or why it can't, with launching only allowed for the
+    /// former.
+    pub fn set_command(&self, command: Result<String, String>) {
+        let (text, is_valid) = match command {
+            Ok(command) => (command, true),
+            Err(e) => (e, false),
+        };
+        self.txt_launch_command
+            .get_buffer()
+            .unwrap()
+            .set_text(&text);
+        self.btn_launch_ok.set_sensitive(is_valid);
+    }
+}
+
+impl Initializable for LaunchDialog {
+    fn init_text(&self, locale: &Locale) {
+        self.dlg_launch.set_title(locale.launch_dialog_title());
+        self.btn_launch_ok
+            .set_label(locale.launch_dialog_play_button());
+        self.btn_launch_cancel
+            .set_label(locale.universal_cancel_button());
+        self.lbl_launch_command
+            .set_label(locale.launch_dialog_command_text());
+        self.lbl_launch_map_options
+            .set_label(locale.launch_dialog_map_options_text());
+    }
+}
@@ -1,7 +1,8 @@
+use crate::connect_whats_new::update_whats_new;
 use crate::gui_data::GuiData;
 use crate::initializable::Initializable;
 use crate::locales::Locale;
-use crate::quake_file::{initialize_data, Files};
+use crate::quake_file::{initialize_data, DatabaseError, Files, QuakeFile};
 use chrono::NaiveDate;
 use glib::Type;
 use glib::{Continue, MainContext, Receiver, Sender, PRIORITY_DEFAULT};
@@ -10,6 +11,8 @@ use gtk::{Builder, ListStore, ScrolledWindow, TreeIter, TreeModel, TreeView};
 use log::*;
 use std::thread;
 
+type DatabaseLoad = (Files, Option<DatabaseError>);
+
 #[derive(Clone)]
 pub struct ListView {
     pub sw_list: ScrolledWindow,
@@ -55,34 +58,36 @@ impl ListView {
     }
 
     fn display_data_in_list(&self, gui_data: &GuiData) {
-        let shared_install_state = gui_data.shared_install_state.clone();
-        let shared_files_state = gui_data.shared_files_state.clone();
-        let shared_images = gui_data.shared_images.clone();
-        let shared_config_state = gui_data.shared_config_state.clone();
-        let col_indices = [0, 1, 2, 3, 4, 5, 6];
-        for file in shared_files_state.borrow().iter() {
-            let rating = match u8::from_str_radix(file.rating(), 10) {
-                Ok(r) => r,
-                Err(_) => 0,
-            };
-            let rating_image = &shared_images.borrow()[rating as usize];
-            let date_format = shared_config_state.borrow().get_date_format();
-            let naive_date = NaiveDate::parse_from_str(file.date(), "%d.%m.%Y").unwrap();
-            let date = naive_date.format(&date_format).to_string();
-            let values: [&dyn ToValue; 7] = [
-                &shared_install_state.borrow().is_map_installed(file.id()),
-                file.id(),
-                file.title(),
-                file.author(),
-                &date,
-                rating_image,
-                &rating,
-            ];
-            self.list_store
-                .set(&self.list_store.append(), &col_indices, &values);
+        // a reload fills the list again from scratch
+        self.list_store.clear();
+        for file in gui_data.shared_files_state.borrow().iter() {
+            self.append_file(gui_data, file);
         }
     }
 
+    pub fn append_file(&self, gui_data: &GuiData, file: &QuakeFile) {
+        let col_indices = [0, 1, 2, 3, 4, 5, 6];
+        let rating: u8 = file.rating().parse().unwrap_or_default();
+        let rating_image = &gui_data.shared_images.borrow()[rating as usize];
+        let date_format = gui_data.shared_config_state.borrow().get_date_format();
+        let naive_date = NaiveDate::parse_from_str(file.date(), "%d.%m.%Y").unwrap();
+        let date = naive_date.format(&date_format).to_string();
+        let values: [&dyn ToValue; 7] = [
+            &gui_data
+                .shared_install_state
+                .borrow()
+                .is_map_installed(file.id()),
+            file.id(),
+            file.title(),
+            file.author(),
+            &date,
+            rating_image,
+            &rating,
+        ];
+        self.list_store
+            .set(&self.list_store.append(), &col_indices, &values);
+    }
+
     fn set_col_title(&self, column: Columns, title: &String) {
         let col = self
             .tree_view
@@ -104,25 +109,50 @@ impl Initializable for ListView {
 
 pub fn populate_list_view(gui_data: &GuiData) {
     trace!("Populating list view");
-    let (sender, receiver): (Sender<Files>, Receiver<Files>) =
+    let (sender, receiver): (Sender<DatabaseLoad>, Receiver<DatabaseLoad>) =
         MainContext::channel(PRIORITY_DEFAULT);
     let rec_gui_data = gui_data.clone();
     let list_view = gui_data.list_view.clone();
     let shared_configs = gui_data.shared_config_state.clone();
     let config_dir = shared_configs.borrow().config_dir().clone();
+    let is_offline = *shared_configs.borrow().is_offline();
+    let mirrors = shared_configs.borrow().mirrors().clone();
     thread::Builder::new()
         .name("List-0".to_string())
         .spawn(move || {
-            let files = initialize_data(config_dir);
-            sender.send(files).expect("Failed to send");
+            let load = initialize_data(config_dir, is_offline, &mirrors);
+            sender.send(load).expect("Failed to send");
         })
         .expect("Failed to spawn thread");
-    receiver.attach(None, move |files| {
+    receiver.attach(None, move |(files, error)| {
         let rec_gui_data = rec_gui_data.clone();
-        let quake_files = files.files().clone();
+        let mut quake_files = files.files().clone();
+        match error {
+            Some(e) => {
+                let config = rec_gui_data.shared_config_state.borrow();
+                rec_gui_data.database_error_bar.show_error(
+                    &e,
+                    !quake_files.is_empty(),
+                    config.current_locale(),
+                );
+            }
+            None => rec_gui_data.database_error_bar.hide(),
+        }
+        // custom installs aren't in the database but belong in the list all the same
+        let custom_files = rec_gui_data
+            .shared_install_state
+            .borrow()
+            .maps()
+            .iter()
+            .filter(|map_pack| map_pack.is_custom())
+            .filter(|map_pack| !quake_files.iter().any(|file| file.id() == map_pack.id()))
+            .map(QuakeFile::custom)
+            .collect::<Vec<QuakeFile>>();
+        quake_files.extend(custom_files);
         let shared_files_state = rec_gui_data.shared_files_state.clone();
         *shared_files_state.borrow_mut() = quake_files;
         list_view.display_data_in_list(&rec_gui_data);
+        update_whats_new(&rec_gui_data);
         Continue(true)
     });
 }
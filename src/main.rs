@@ -1,38 +1,60 @@
+mod channel_reporter;
 mod clear_cache_dialog;
+mod cli;
 mod config_dialog;
-mod configuration;
 mod connect_config_dialog;
+mod connect_custom_install;
 mod connect_detail_buttons;
+mod connect_download_queue;
+mod connect_integrity;
 mod connect_menu_options;
 mod connect_output_dialog;
 mod connect_search_event;
 mod connect_selection_change;
+mod connect_whats_new;
+mod custom_install_dialog;
+mod database_error_bar;
 mod detail_pane;
-mod download_progress;
 mod engine_config_tab;
+mod engines_config_tab;
 mod filter_bar;
-mod game_player;
 mod gui_data;
-mod image_loader;
 mod initializable;
 mod initialize_gui;
-mod installer;
+mod launch_dialog;
 mod list_view;
-mod locales;
 mod main_menu;
+mod network_config_tab;
 mod output_dialog;
 mod personal_config_tab;
 mod progress_dialog;
-mod quake_file;
-mod request_utils;
+mod queue_panel;
 
 use dirs::config_dir;
 use flexi_logger::{Age, Cleanup, Criterion, LevelFilter, LogSpecBuilder, Logger, Naming};
 use initialize_gui::initialize_gui;
 use log::*;
+use rq_injector_core::{
+    configuration, database_refresh, dependencies, download_error, download_progress,
+    download_queue, game_player, http, image_loader, install_detection, installer, integrity,
+    launch_args, locales, quake_file, quake_paths, whats_new,
+};
 use std::path::PathBuf;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let is_headless = !args.is_empty();
+    let first_time_init = initialize_config_dirs();
+    initialize_logger(is_headless);
+    if is_headless {
+        std::process::exit(cli::run(args));
+    }
+    gtk::init().expect("Failed to init gtk");
+    initialize_application(first_time_init);
+    gtk::main();
+}
+
+fn initialize_logger(is_headless: bool) {
     let mut log_builder = LogSpecBuilder::new();
     log_builder.default(LevelFilter::Debug);
     log_builder.module("reqwest", LevelFilter::Debug);
@@ -42,8 +64,16 @@ fn main() {
     log_dir.push("QInjector");
     log_dir.push("logs");
 
-    Logger::with(log_builder.build())
-        .duplicate_to_stdout(flexi_logger::Duplicate::All)
+    // stdout belongs to the command output when running headless
+    let logger = match is_headless {
+        true => Logger::with(log_builder.build())
+            .duplicate_to_stderr(flexi_logger::Duplicate::Warn)
+            .format_for_stderr(flexi_logger::default_format),
+        false => {
+            Logger::with(log_builder.build()).duplicate_to_stdout(flexi_logger::Duplicate::All)
+        }
+    };
+    logger
         .log_to_file()
         .format(flexi_logger::colored_with_thread)
         .o_directory(Some(log_dir))
@@ -55,13 +85,16 @@ fn main() {
         .set_palette("196;208;-;7;10".to_string())
         .start()
         .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
-    gtk::init().expect("Failed to init gtk");
-    initialize_application();
-    gtk::main();
 }
 
-fn initialize_application() {
+fn initialize_application(first_time_init: bool) {
     trace!("Starting application");
+    // if we needed to remake directories pop the init modal
+    initialize_gui(first_time_init);
+}
+
+// returns true if any of the directories had to be created
+fn initialize_config_dirs() -> bool {
     let mut base_config_dir = config_dir().expect("No config dir found");
     base_config_dir.push("QInjector");
     let base_needed = match std::fs::create_dir(&base_config_dir) {
@@ -74,11 +107,9 @@ fn initialize_application() {
             false
         }
     };
-    let images_needed = init_dir_by_name(&mut base_config_dir, "images");
+    let images_needed = init_dir_by_name(&mut base_config_dir.clone(), "images");
     let logs_needed = init_dir_by_name(&mut base_config_dir, "logs");
-
-    // if we needed to remake directories pop the init modal
-    initialize_gui(base_needed || images_needed || logs_needed);
+    base_needed || images_needed || logs_needed
 }
 
 fn init_dir_by_name(config_dir: &mut PathBuf, name: &str) -> bool {
@@ -7,9 +7,13 @@ use gtk::{Builder, CheckMenuItem, MenuBar, MenuItem};
 pub struct MainMenu {
     pub menu_bar: MenuBar,
     pub menu_reload: MenuItem,
+    pub menu_install_file: MenuItem,
+    pub menu_install_url: MenuItem,
     pub menu_check_for_installed: MenuItem,
     pub menu_offline: CheckMenuItem,
     pub menu_clear_cache: MenuItem,
+    pub menu_verify_cache: MenuItem,
+    pub menu_verify_installs: MenuItem,
     pub menu_quit: MenuItem,
     pub menu_engine_configuration: MenuItem,
     pub menu_file: MenuItem,
@@ -24,12 +28,24 @@ impl MainMenu {
         let menu_reload: MenuItem = builder
             .get_object("menu_reload")
             .expect("Failed to get menu_reload");
+        let menu_install_file: MenuItem = builder
+            .get_object("menu_install_file")
+            .expect("Failed to get menu_install_file");
+        let menu_install_url: MenuItem = builder
+            .get_object("menu_install_url")
+            .expect("Failed to get menu_install_url");
         let menu_check_for_installed: MenuItem = builder
             .get_object("menu_check_for_installed")
             .expect("Failed to get menu_check_for_installed");
         let menu_clear_cache: MenuItem = builder
             .get_object("menu_clear_cache")
             .expect("Failed to get menu_clear_cache");
+        let menu_verify_cache: MenuItem = builder
+            .get_object("menu_verify_cache")
+            .expect("Failed to get menu_verify_cache");
+        let menu_verify_installs: MenuItem = builder
+            .get_object("menu_verify_installs")
+            .expect("Failed to get menu_verify_installs");
         let menu_offline: CheckMenuItem = builder
             .get_object("menu_offline")
             .expect("Failed to get menu_offline");
@@ -48,11 +64,15 @@ impl MainMenu {
         Self {
             menu_bar,
             menu_reload,
+            menu_install_file,
+            menu_install_url,
             menu_check_for_installed,
             menu_offline,
             menu_quit,
             menu_engine_configuration,
             menu_clear_cache,
+            menu_verify_cache,
+            menu_verify_installs,
             menu_file,
             menu_config,
         }
@@ -60,16 +80,25 @@ impl MainMenu {
 
     pub fn init_states(&self, is_offline: bool) {
         self.menu_reload.set_sensitive(!is_offline);
+        self.menu_install_url.set_sensitive(!is_offline);
         self.menu_offline.set_active(is_offline);
     }
 }
 
 impl Initializable for MainMenu {
     fn init_text(&self, locale: &Locale) {
+        self.menu_install_file
+            .set_label(locale.install_file_menu_option());
+        self.menu_install_url
+            .set_label(locale.install_url_menu_option());
         self.menu_check_for_installed
             .set_label(locale.check_installed_menu_option());
         self.menu_clear_cache
             .set_label(locale.clear_cache_menu_option());
+        self.menu_verify_cache
+            .set_label(locale.verify_cache_menu_option());
+        self.menu_verify_installs
+            .set_label(locale.verify_installs_menu_option());
         self.menu_engine_configuration
             .set_label(locale.configuration_menu_option());
         self.menu_offline.set_label(locale.offline_menu_option());
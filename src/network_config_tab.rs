@@ -0,0 +1,124 @@
+use crate::configuration::Configuration;
+use crate::initializable::Initializable;
+use crate::locales::Locale;
+use gtk::prelude::*;
+use gtk::{Builder, Entry, Label, SpinButton};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct NetworkConfigTab {
+    pub tab_network: Label,
+
+    pub ent_proxy: Entry,
+    pub ent_proxy_user: Entry,
+    pub ent_proxy_password: Entry,
+
+    pub lbl_network_title: Label,
+    pub lbl_config_network_proxy: Label,
+    pub lbl_config_network_proxy_user: Label,
+    pub lbl_config_network_proxy_password: Label,
+    pub lbl_config_network_connect_timeout: Label,
+    pub lbl_config_network_read_timeout: Label,
+    pub lbl_config_network_retries: Label,
+
+    pub spin_connect_timeout: SpinButton,
+    pub spin_read_timeout: SpinButton,
+    pub spin_retries: SpinButton,
+}
+
+impl NetworkConfigTab {
+    pub fn create_from_builder(builder: &Builder) -> Self {
+        let tab_network: Label = builder
+            .get_object("tab_network")
+            .expect("Failed to get tab_network");
+        let ent_proxy: Entry = builder
+            .get_object("ent_proxy")
+            .expect("Failed to get ent_proxy");
+        let ent_proxy_user: Entry = builder
+            .get_object("ent_proxy_user")
+            .expect("Failed to get ent_proxy_user");
+        let ent_proxy_password: Entry = builder
+            .get_object("ent_proxy_password")
+            .expect("Failed to get ent_proxy_password");
+        let lbl_network_title: Label = builder
+            .get_object("lbl_network_title")
+            .expect("Failed to get lbl_network_title");
+        let lbl_config_network_proxy: Label = builder
+            .get_object("lbl_config_network_proxy")
+            .expect("Failed to get lbl_config_network_proxy");
+        let lbl_config_network_proxy_user: Label = builder
+            .get_object("lbl_config_network_proxy_user")
+            .expect("Failed to get lbl_config_network_proxy_user");
+        let lbl_config_network_proxy_password: Label = builder
+            .get_object("lbl_config_network_proxy_password")
+            .expect("Failed to get lbl_config_network_proxy_password");
+        let lbl_config_network_connect_timeout: Label = builder
+            .get_object("lbl_config_network_connect_timeout")
+            .expect("Failed to get lbl_config_network_connect_timeout");
+        let lbl_config_network_read_timeout: Label = builder
+            .get_object("lbl_config_network_read_timeout")
+            .expect("Failed to get lbl_config_network_read_timeout");
+        let lbl_config_network_retries: Label = builder
+            .get_object("lbl_config_network_retries")
+            .expect("Failed to get lbl_config_network_retries");
+        let spin_connect_timeout: SpinButton = builder
+            .get_object("spin_connect_timeout")
+            .expect("Failed to get spin_connect_timeout");
+        let spin_read_timeout: SpinButton = builder
+            .get_object("spin_read_timeout")
+            .expect("Failed to get spin_read_timeout");
+        let spin_retries: SpinButton = builder
+            .get_object("spin_retries")
+            .expect("Failed to get spin_retries");
+        Self {
+            tab_network,
+            ent_proxy,
+            ent_proxy_user,
+            ent_proxy_password,
+            lbl_network_title,
+            lbl_config_network_proxy,
+            lbl_config_network_proxy_user,
+            lbl_config_network_proxy_password,
+            lbl_config_network_connect_timeout,
+            lbl_config_network_read_timeout,
+            lbl_config_network_retries,
+            spin_connect_timeout,
+            spin_read_timeout,
+            spin_retries,
+        }
+    }
+
+    pub fn show(&self, shared_config: Rc<RefCell<Configuration>>) {
+        let borrow = shared_config.borrow();
+        self.ent_proxy.set_text(borrow.proxy());
+        self.ent_proxy_user.set_text(borrow.proxy_username());
+        self.ent_proxy_password.set_text(borrow.proxy_password());
+        self.spin_connect_timeout
+            .set_value(*borrow.connect_timeout_secs() as f64);
+        self.spin_read_timeout
+            .set_value(*borrow.read_timeout_secs() as f64);
+        self.spin_retries.set_value(*borrow.retries() as f64);
+    }
+}
+
+impl Initializable for NetworkConfigTab {
+    fn init_text(&self, locale: &Locale) {
+        self.tab_network
+            .set_label(locale.config_dialog_network_tab());
+        self.lbl_network_title
+            .set_label(locale.config_dialog_network_title());
+        self.lbl_config_network_proxy
+            .set_label(locale.config_dialog_proxy_text());
+        self.lbl_config_network_proxy_user
+            .set_label(locale.config_dialog_proxy_user_text());
+        self.lbl_config_network_proxy_password
+            .set_label(locale.config_dialog_proxy_password_text());
+        self.lbl_config_network_connect_timeout
+            .set_label(locale.config_dialog_connect_timeout_text());
+        self.lbl_config_network_read_timeout
+            .set_label(locale.config_dialog_read_timeout_text());
+        self.lbl_config_network_retries
+            .set_label(locale.config_dialog_retries_text());
+    }
+}
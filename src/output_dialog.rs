@@ -1,12 +1,14 @@
 use crate::initializable::Initializable;
 use crate::locales::Locale;
 use gtk::prelude::*;
-use gtk::{Builder, Button, Dialog, TextView};
+use gtk::{Builder, Button, Dialog, Label, TextView};
 
 #[derive(Clone)]
 pub struct OutputDialog {
     pub dlg_output: Dialog,
     pub btn_ok: Button,
+    pub btn_kill_game: Button,
+    pub lbl_game_status: Label,
     pub txt_output: TextView,
 }
 
@@ -18,20 +20,68 @@ impl OutputDialog {
         let btn_ok: Button = builder
             .get_object("btn_confirm_game_output")
             .expect("Failed to get btn_ok");
+        let btn_kill_game: Button = builder
+            .get_object("btn_kill_game")
+            .expect("Failed to get btn_kill_game");
+        let lbl_game_status: Label = builder
+            .get_object("lbl_game_status")
+            .expect("Failed to get lbl_game_status");
         let txt_output: TextView = builder
             .get_object("txt_output")
             .expect("Failed to get txt_output");
         Self {
             dlg_output,
             btn_ok,
+            btn_kill_game,
+            lbl_game_status,
             txt_output,
         }
     }
+
+    pub fn show_text(&self, text: &str, locale: &Locale) {
+        self.txt_output.get_buffer().unwrap().set_text(text);
+        self.init_text(locale);
+        self.btn_kill_game.set_visible(false);
+        self.lbl_game_status.set_visible(false);
+        self.dlg_output.show_all();
+    }
+
+    /// Opens the dialog empty for a game that's about to print into it.
+    pub fn start_game(&self, command_line: &str, locale: &Locale) {
+        self.txt_output.get_buffer().unwrap().set_text("");
+        self.init_text(locale);
+        self.lbl_game_status
+            .set_text(&format!("{} {}", locale.game_running_text(), command_line));
+        self.lbl_game_status.set_visible(true);
+        self.btn_kill_game.set_sensitive(true);
+        self.btn_kill_game.set_visible(true);
+        self.dlg_output.show_all();
+    }
+
+    pub fn append_line(&self, line: &str) {
+        let buffer = self.txt_output.get_buffer().unwrap();
+        let mut end = buffer.get_end_iter();
+        if buffer.get_char_count() > 0 {
+            buffer.insert(&mut end, "\n");
+        }
+        buffer.insert(&mut end, line);
+        // keep the newest line in view
+        if let Some(mark) = buffer.create_mark(None, &buffer.get_end_iter(), false) {
+            self.txt_output.scroll_mark_onscreen(&mark);
+            buffer.delete_mark(&mark);
+        }
+    }
+
+    pub fn set_game_status(&self, text: &str) {
+        self.lbl_game_status.set_text(text);
+        self.btn_kill_game.set_sensitive(false);
+    }
 }
 
 impl Initializable for OutputDialog {
     fn init_text(&self, locale: &Locale) {
         self.btn_ok.set_label(locale.universal_ok_button());
+        self.btn_kill_game.set_label(locale.kill_game_button());
         self.dlg_output.set_title(locale.output_dialog_title());
     }
 }
@@ -0,0 +1,141 @@
+use crate::download_queue::QueueState;
+use crate::locales::Locale;
+use gtk::prelude::*;
+use gtk::{Builder, Button, Label, ProgressBar};
+use log::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct QueuePanel {
+    pub box_progress: gtk::Box,
+    rows: Rc<RefCell<Vec<QueueRow>>>,
+}
+
+#[derive(Clone)]
+pub struct QueueRow {
+    pub map_id: String,
+    pub box_row: gtk::Box,
+    pub progress_bar: ProgressBar,
+    pub lbl_state: Label,
+    pub btn_cancel: Button,
+    pub btn_retry: Button,
+}
+
+impl QueuePanel {
+    pub fn create_from_builder(builder: &Builder) -> Self {
+        trace!("Initializing queue panel");
+        let box_progress: gtk::Box = builder
+            .get_object("box_progress")
+            .expect("Failed to get box_progress");
+        Self {
+            box_progress,
+            rows: Rc::new(RefCell::new(vec![])),
+        }
+    }
+
+    /// Adds a row for the map, or hands back the existing one if it is already shown.
+    pub fn add_row(&self, map_id: &String, locale: &Locale) -> QueueRow {
+        if let Some(row) = self.get_row(map_id) {
+            return row;
+        }
+        let box_row = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        let progress_bar = ProgressBar::new();
+        progress_bar.set_text(Some(map_id));
+        progress_bar.set_show_text(true);
+        progress_bar.set_hexpand(true);
+        let lbl_state = Label::new(None);
+        let btn_cancel = Button::with_label(locale.universal_cancel_button());
+        let btn_retry = Button::with_label(locale.queue_retry_button());
+        box_row.pack_start(&progress_bar, true, true, 0);
+        box_row.pack_start(&lbl_state, false, false, 0);
+        box_row.pack_start(&btn_retry, false, false, 0);
+        box_row.pack_start(&btn_cancel, false, false, 0);
+        self.box_progress.add(&box_row);
+        let row = QueueRow {
+            map_id: map_id.to_owned(),
+            box_row,
+            progress_bar,
+            lbl_state,
+            btn_cancel,
+            btn_retry,
+        };
+        self.rows.borrow_mut().push(row.clone());
+        self.box_progress.show_all();
+        row
+    }
+
+    pub fn update_progress(&self, map_id: &String, file_name: &str, fraction: f64) {
+        if let Some(row) = self.get_row(map_id) {
+            row.progress_bar.set_fraction(fraction.min(1.0));
+            // dependencies report under their own file name
+            let file_id = file_name.trim_end_matches(".zip");
+            let text = match file_id == map_id.as_str() {
+                true => map_id.to_owned(),
+                false => format!("{} ({})", map_id, file_id),
+            };
+            row.progress_bar.set_text(Some(&text));
+        }
+    }
+
+    pub fn set_resumed(&self, map_id: &String, resumed_bytes: u64) {
+        if let Some(row) = self.get_row(map_id) {
+            let megabytes = resumed_bytes as f64 / 1_000_000.0;
+            let text = format!("Resumed at {:.1} mb", megabytes);
+            row.progress_bar.set_tooltip_text(Some(&text));
+        }
+    }
+
+    pub fn update_state(&self, map_id: &String, state: &QueueState, locale: &Locale) {
+        let row = match self.get_row(map_id) {
+            Some(row) => row,
+            None => return,
+        };
+        let state_text = match state {
+            QueueState::Queued => locale.queue_state_queued(),
+            QueueState::Running => locale.queue_state_running(),
+            QueueState::Failed(_) => locale.queue_state_failed(),
+            QueueState::Done => locale.queue_state_done(),
+            QueueState::Cancelled => locale.queue_state_cancelled(),
+        };
+        row.lbl_state.set_text(state_text);
+        match state {
+            QueueState::Failed(e) => row.lbl_state.set_tooltip_text(Some(e)),
+            _ => row.lbl_state.set_tooltip_text(None),
+        }
+        if let QueueState::Queued = state {
+            row.progress_bar.set_text(Some(map_id));
+            row.progress_bar.set_fraction(0.0);
+        }
+        // once an item is finished the cancel button just clears it from the list
+        let cancel_text = match state.is_finished() {
+            true => locale.queue_remove_button(),
+            false => locale.universal_cancel_button(),
+        };
+        row.btn_cancel.set_label(cancel_text);
+        row.btn_retry.set_visible(matches!(
+            state,
+            QueueState::Failed(_) | QueueState::Cancelled
+        ));
+    }
+
+    pub fn remove_row(&self, map_id: &String) {
+        let mut rows = self.rows.borrow_mut();
+        if let Some(pos) = rows.iter().position(|row| &row.map_id == map_id) {
+            let row = rows.remove(pos);
+            self.box_progress.remove(&row.box_row);
+        }
+    }
+
+    pub fn has_row(&self, map_id: &String) -> bool {
+        self.get_row(map_id).is_some()
+    }
+
+    fn get_row(&self, map_id: &String) -> Option<QueueRow> {
+        self.rows
+            .borrow()
+            .iter()
+            .find(|row| &row.map_id == map_id)
+            .cloned()
+    }
+}